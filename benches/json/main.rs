@@ -1,9 +1,11 @@
 mod json_daisychain;
 mod json_nom;
 
-use std::collections::HashMap;
+use std::{cell::Cell, collections::HashMap, rc::Rc};
 
 use criterion::{black_box, criterion_group, Criterion};
+use daisychain::contrib::json;
+use daisychain::trace::{with_tracer, Event, Tracer};
 use json_daisychain::daisychain_parser;
 use json_nom::nom_parser;
 use log::{trace};
@@ -34,7 +36,36 @@ pub fn bench_daisychain(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_nom, bench_daisychain);
+pub fn bench_contrib(c: &mut Criterion) {
+    c.bench_function("json_contrib", |b| b.iter(|| json::parse(black_box(JSON))));
+}
+
+#[derive(Default)]
+struct CountingTracer(Cell<usize>);
+
+impl Tracer for CountingTracer {
+    fn event(&self, _event: &Event) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+// run with and without `--features trace` to compare the cost of the tracing hooks
+pub fn bench_tracing(c: &mut Criterion) {
+    let feature = if cfg!(feature = "trace") { "on" } else { "off" };
+    c.bench_function(&format!("json_daisychain_trace_{feature}"), |b| {
+        b.iter(|| daisychain_parser(black_box(JSON)))
+    });
+    if cfg!(feature = "trace") {
+        let tracer = Rc::new(CountingTracer::default());
+        with_tracer(tracer, || {
+            c.bench_function("json_daisychain_trace_on_with_tracer", |b| {
+                b.iter(|| daisychain_parser(black_box(JSON)))
+            })
+        });
+    }
+}
+
+criterion_group!(benches, bench_nom, bench_daisychain, bench_contrib, bench_tracing);
 // criterion_main!(benches);
 
 fn main() {
@@ -1,10 +1,14 @@
 use std::str::FromStr;
 use std::{fmt::Debug, marker::PhantomData};
 
-use log::Level::Trace;
-use log::{log_enabled, trace};
+#[cfg(feature = "trace")]
+use log::trace;
 
-use crate::{prelude::ParsingError, LABEL, LOG_TARGET};
+use crate::{
+    context,
+    describe::{short_type_name, Arg, Describe, Node},
+    prelude::ParsingError,
+};
 
 fn type_suffix(type_name: &str) -> &str {
     if let Some(i) = type_name.rfind("::") {
@@ -33,9 +37,9 @@ pub trait Parser<'a>: Sized {
     //     self.parse(inp).map(|(i,t)| (i,t.detuple()))
     // }
 
-    fn chain_parser<T, P2: Parser<'a, Output = T>>(self, p2: P2) -> Chain<'a, Self, P2>
+    fn chain_parser<T, P2>(self, p2: P2) -> Chain<'a, Self, P2>
     where
-        P2: Parser<'a, Input = Self::Input, Error = Self::Error>,
+        P2: Parser<'a, Output = T, Input = Self::Input, Error = Self::Error>,
         // Self::Input: Clone,
         // P2: Parser<'a, Input = Self::Input, Error = Self::Error>,
         // (Self::Output, T): ConcatTuple<Self::Output, P2::Input>,
@@ -57,17 +61,12 @@ type Lexer = for<'b> fn(&'b str) -> Result<&'b str, ParsingError>;
 impl<'a> StrParser<'a, &'a str> for Lexer {}
 
 pub trait StrParser<'a, O>: Parser<'a, Input = &'a str, Output = O, Error = ParsingError> {
-    fn ws(self) -> Chain<'a, Self, Lexer> {
-        self.chain_parser(func_ws)
+    fn ws(self) -> Chain<'a, Self, Ws> {
+        self.chain_parser(Ws)
     }
 
     fn debug_context(self, span_name: &'static str) -> Self {
-        if log_enabled!(target: LOG_TARGET, Trace) {
-            LABEL.with(|f| f.set("")); // blank the span name before logging
-                                       // self.log_success("debug_context", span_name);
-            LABEL.with(|f| f.set(span_name));
-        }
-
+        context::set(span_name);
         self
     }
 
@@ -104,13 +103,7 @@ where
     fn validate(&mut self, inp: Self::Input) -> Result<Self::Output, Self::Error> {
         match inp.parse::<T>() {
             Ok(t) => Ok((inp, t)),
-            Err(..) => {
-                let e = ParsingError::NoMatch {
-                    action: "FromStr",
-                    args: "",
-                };
-                Err(e)
-            }
+            Err(_) => Err(ParsingError::conversion_of::<T>(inp)),
         }
     }
 }
@@ -216,13 +209,16 @@ where
 
     fn validate(&mut self, inp: Self::Input) -> Result<Self::Output, Self::Error> {
         let o1: P1::Output = self.p1.validate(inp.clone())?;
+        #[cfg(feature = "trace")]
         trace!("o1: {p1}({inp:?}) -> Ok({o1:?})", p1 = self.p1.name(""));
         let s = <(P1::Output, P2::Output) as ConcatTuple<P1::Output, P2::Input>>::input2_from(
             o1.clone(),
         );
         let o2: P2::Output = self.p2.validate(s.clone())?;
+        #[cfg(feature = "trace")]
         trace!("o2: {p2}({s:?}) -> Ok({o2:?})", p2 = self.p2.name(""));
         let o12: Self::Output = ConcatTuple::concat((o1, o2));
+        #[cfg(feature = "trace")]
         trace!("o12: {o12:?}");
         Ok(o12)
     }
@@ -428,9 +424,73 @@ impl<'a> Parser<'a> for SP {
     }
 }
 
+/// skips any whitespace, as `Matchable::ws`
+pub struct Ws;
+
+impl<'a> StrParser<'a, &'a str> for Ws {}
+
+impl<'a> Parser<'a> for Ws {
+    type Input = &'a str;
+    type Error = ParsingError;
+    type Output = &'a str;
+
+    fn validate(&mut self, inp: Self::Input) -> Result<Self::Input, Self::Error> {
+        func_ws(inp)
+    }
+}
+
+impl<'a, P1: Describe, P2: Describe> Describe for Chain<'a, P1, P2> {
+    fn describe(&self) -> Node {
+        Node::seq([self.p1.describe(), self.p2.describe()])
+    }
+}
+
+impl<'a, P: Describe> Describe for Find<'a, P> {
+    fn describe(&self) -> Node {
+        let find = Node::new("find").arg(Arg::Text(self.needle.to_string()));
+        Node::seq([self.chain.describe(), find])
+    }
+}
+
+impl<'a, T> Describe for ParseSelection<'a, T> {
+    fn describe(&self) -> Node {
+        Node::new("parse_selection").arg(Arg::Name(short_type_name(std::any::type_name::<T>())))
+    }
+}
+
+impl Describe for Ws {
+    fn describe(&self) -> Node {
+        Node::new("ws")
+    }
+}
+
+impl Describe for SP {
+    fn describe(&self) -> Node {
+        Node::seq([])
+    }
+}
+
+impl<F> Describe for Par<F> {
+    fn describe(&self) -> Node {
+        Node::new("parse_with").arg(Arg::Name(short_type_name(std::any::type_name::<F>())))
+    }
+}
+
+impl<F> Describe for F
+where
+    F: for<'s> FnMut(&'s str) -> Result<&'s str, ParsingError>,
+{
+    fn describe(&self) -> Node {
+        match short_type_name(std::any::type_name::<F>()) {
+            // fn pointers are unnamed
+            name if name.contains('(') => Node::new("lexer"),
+            name => Node::new("lexer").arg(Arg::Name(name)),
+        }
+    }
+}
+
 /// (a, (b,c)) -> (a,b,c)
 /// (a, (b, (c,d))) ->
-
 pub trait DeTuple {
     type Output;
     fn detuple(self) -> Self::Output;
@@ -460,6 +520,22 @@ mod tests {
         prelude::ParsingError,
     };
     use test_log::test;
+    #[test]
+    fn test_describe() {
+        use crate::{combo::StrParser, describe::Describe};
+        fn tail(s: &str) -> Result<&str, ParsingError> {
+            Ok(&s[1..])
+        }
+        fn num(s: &str) -> Result<(&str, i32), ParsingError> {
+            Ok((&s[1..], s[0..=0].parse::<i32>()?))
+        }
+        let p = SP.ws().find("x").chain_parser(tail).chain_parser(Par(num));
+        assert_eq!(
+            p.describe().to_text(),
+            "seq\n├── ws\n├── find \"x\"\n├── lexer tail\n└── parse_with num\n"
+        );
+    }
+
     #[test]
     fn test_detuple() {
         println!("{:?}", (1, (2, 3, ())).detuple());
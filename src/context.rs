@@ -0,0 +1,258 @@
+use std::{cell::RefCell, fmt};
+
+// the debug_context labels of the cursors in progress, outermost first. A cursor's
+// label sits at the stack length when it was created, and is discarded by its
+// `validate`, along with those of any cursors created since. Parsers on other threads
+// have their own stack.
+thread_local!(static STACK: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) });
+
+/// a nested parse, discarding any labels it left behind (eg by returning early with `?`)
+/// when dropped
+pub(crate) struct Scope {
+    prior_len: usize,
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        end(self.prior_len);
+    }
+}
+
+/// called on entry to a nested parser (`parse_with`, grammar rules etc)
+pub(crate) fn enter() -> Scope {
+    Scope { prior_len: base() }
+}
+
+/// the position in the stack of the label of a cursor created now
+pub(crate) fn base() -> usize {
+    STACK.with(|st| st.borrow().len())
+}
+
+/// sets the label of the cursor created at `base`, replacing any earlier one
+pub(crate) fn set_at(base: usize, label: &'static str) {
+    STACK.with(|st| {
+        let mut stack = st.borrow_mut();
+        stack.resize(base, "");
+        stack.push(label);
+    })
+}
+
+/// discards the label of the cursor created at `base`, and any set since
+pub(crate) fn end(base: usize) {
+    STACK.with(|st| st.borrow_mut().truncate(base))
+}
+
+/// sets the label of the innermost parse, for parsers without a cursor
+pub(crate) fn set(label: &'static str) {
+    STACK.with(|st| {
+        let mut stack = st.borrow_mut();
+        match stack.last_mut() {
+            Some(top) => *top = label,
+            None => stack.push(label),
+        }
+    })
+}
+
+/// whether a context path is `outer`, or nested within it
+pub(crate) fn is_within(path: &str, outer: &str) -> bool {
+    path == outer || path.starts_with(outer) && path[outer.len()..].starts_with(" > ")
+}
+
+/// The `debug_context` labels of the parsers in progress on this thread, outermost first,
+/// eg "json_value > hash > key_value". Nested parsers without a label are omitted.
+pub fn context_path() -> String {
+    STACK.with(|st| {
+        st.borrow()
+            .iter()
+            .filter(|l| !l.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" > ")
+    })
+}
+
+/// The `debug_context` path of a trace event, as `context_path`. The labels are only
+/// read when it is formatted, so only mean anything within `Tracer::event`
+#[derive(Clone, Copy)]
+pub struct ContextPath {
+    _private: (),
+}
+
+impl ContextPath {
+    pub(crate) fn current() -> Self {
+        Self { _private: () }
+    }
+}
+
+impl fmt::Display for ContextPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        STACK.with(|st| {
+            let stack = st.borrow();
+            let mut labels = stack.iter().filter(|l| !l.is_empty());
+            if let Some(first) = labels.next() {
+                f.write_str(first)?;
+            }
+            labels.try_for_each(|l| write!(f, " > {l}"))
+        })
+    }
+}
+
+impl fmt::Debug for ContextPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+/// the innermost `debug_context` label on this thread
+pub fn context_label() -> &'static str {
+    STACK.with(|st| {
+        st.borrow()
+            .iter()
+            .rev()
+            .find(|l| !l.is_empty())
+            .copied()
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use test_log::test;
+
+    fn key_value(s: &str) -> Result<(&str, String), ParsingError> {
+        let c = Cursor::from(s)
+            .debug_context("key_value")
+            .alphabetics(1..)
+            .parse_selection_as_str()
+            .text("=");
+        let path = context_path();
+        c.validate().map(|(c, k)| (c, format!("{k}@{path}")))
+    }
+
+    fn hash(s: &str) -> Result<(&str, Vec<String>), ParsingError> {
+        let mut vec = vec![];
+        let c = Cursor::from(s)
+            .debug_context("hash")
+            .text("{")
+            .parse_struct_vec_to(key_value, &mut vec)?
+            .text("}")
+            .validate()?;
+        Ok((c, vec))
+    }
+
+    #[test]
+    fn test_context_path() {
+        let (_c, keys) = Cursor::from("{a=b=}")
+            .debug_context("json_value")
+            .parse_with(hash)
+            .validate()
+            .unwrap();
+        assert_eq!(
+            keys,
+            [
+                "a@json_value > hash > key_value",
+                "b@json_value > hash > key_value"
+            ]
+        );
+        // discarded by the validate of the cursor that set it
+        assert_eq!(context_path(), "");
+        assert_eq!(context_label(), "");
+    }
+
+    #[test]
+    fn test_context_ends_with_parse() {
+        let res = Cursor::from("abc")
+            .debug_context("address")
+            .digits(1..)
+            .validate();
+        assert_eq!(res.is_err(), true);
+        let e = Cursor::from("99999")
+            .digits(1..)
+            .parse_selection::<u16>()
+            .validate()
+            .unwrap_err();
+        assert_eq!(e.to_string(), "Conversion: (text='99999' type='u16')");
+
+        // a cursor discarded without validate keeps its label until the enclosing parse returns
+        let res = Cursor::from("300")
+            .parse_with(|s| {
+                let _ = Cursor::from(s).debug_context("discarded");
+                Cursor::from(s)
+                    .digits(1..)
+                    .parse_selection::<u8>()
+                    .validate()
+            })
+            .validate();
+        assert_eq!(res.is_err(), true);
+        assert_eq!(context_path(), "");
+
+        let res = Limits::new().parse("1", |s| {
+            Cursor::from(s)
+                .debug_context("limited")
+                .digits(1..)
+                .parse_selection::<u8>()
+                .validate()
+        });
+        assert_eq!(res.unwrap(), ("", 1));
+        assert_eq!(context_path(), "");
+    }
+
+    #[test]
+    fn test_context_direct_call() {
+        // a helper called directly, rather than through parse_with, nests its label
+        let c = Cursor::from("{a=}").debug_context("outer").text("{");
+        assert_eq!(context_path(), "outer");
+        let (rest, key) = key_value(c.str().unwrap()).unwrap();
+        assert_eq!(key, "a@outer > key_value");
+        assert_eq!(context_path(), "outer");
+        let res = c.set_str(rest).text("}").validate();
+        assert_eq!(res.unwrap(), "");
+        assert_eq!(context_path(), "");
+    }
+
+    #[test]
+    fn test_error_context() {
+        fn port(s: &str) -> Result<(&str, u16), ParsingError> {
+            Cursor::from(s)
+                .debug_context("port")
+                .digits(1..)
+                .parse_selection_keep_source()
+                .validate()
+        }
+        let e = Cursor::from("host:99999")
+            .debug_context("address")
+            .alphabetics(1..)
+            .text(":")
+            .parse_with(port)
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Conversion: (text='99999' type='u16' context='address > port') number too large to fit in target type"
+        );
+    }
+
+    #[test]
+    fn test_context_threads() {
+        let handles: Vec<_> = ["a", "b", "c"]
+            .into_iter()
+            .map(|label| {
+                std::thread::spawn(move || {
+                    let (_c, keys) = Cursor::from("{k=}")
+                        .debug_context(label)
+                        .parse_with(hash)
+                        .validate()
+                        .unwrap();
+                    (keys, context_path())
+                })
+            })
+            .collect();
+        for (h, label) in handles.into_iter().zip(["a", "b", "c"]) {
+            let (keys, path) = h.join().unwrap();
+            assert_eq!(keys, [format!("k@{label} > hash > key_value")]);
+            assert_eq!(path, "");
+        }
+    }
+}
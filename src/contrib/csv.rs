@@ -0,0 +1,456 @@
+//! Delimited text (CSV, TSV etc) following [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180).
+//!
+//! Fields may be quoted, with a doubled quote standing for a quote, and quoted fields
+//! may contain delimiters and newlines. Records end with `\n` or `\r\n`, and blank
+//! lines between records are skipped. Fields borrow from the input unless they
+//! contain a doubled quote.
+//!
+//! ```
+//! use daisychain::contrib::csv::{Dialect, Trim};
+//!
+//! let text = "name, notes\nandy, \"likes \"\"quotes\"\"\"\nbea, \"two\nlines\"\n";
+//! let mut records = Dialect::new().trim(Trim::All).records(text);
+//! assert_eq!(records.headers().unwrap().unwrap(), &["name", "notes"]);
+//!
+//! let rows: Vec<_> = records.collect::<Result<_, _>>().unwrap();
+//! assert_eq!(rows, [["andy", "likes \"quotes\""], ["bea", "two\nlines"]]);
+//! ```
+//!
+//! Rows can instead be converted with `FromStr` from the record's text, as
+//! [`parse_selection`](crate::prelude::Matchable::parse_selection) does.
+//! [`Dialect::lex_record`] selects a record within any other parser.
+//!
+//! ```
+//! use std::str::FromStr;
+//! use daisychain::contrib::csv::Dialect;
+//! use daisychain::prelude::*;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Point(i32, i32);
+//!
+//! impl FromStr for Point {
+//!     type Err = ParsingError;
+//!
+//!     fn from_str(s: &str) -> Result<Self, Self::Err> {
+//!         let (_, fields) = Dialect::new().record(s)?;
+//!         Ok(Point(fields[0].parse()?, fields[1].parse()?))
+//!     }
+//! }
+//!
+//! let points = Dialect::new().records("x,y\n1,2\n-3,4\n").rows::<Point>();
+//! assert_eq!(points.collect::<Result<Vec<_>, _>>().unwrap(), [Point(1, 2), Point(-3, 4)]);
+//! ```
+use std::{borrow::Cow, fmt::Debug, str::FromStr};
+
+use crate::prelude::*;
+
+/// The fields of a record, borrowed unless unescaped
+pub type Record<'a> = Vec<Cow<'a, str>>;
+
+/// Which records have spaces and tabs around their fields removed.
+/// Quoted fields keep the whitespace within their quotes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Trim {
+    #[default]
+    None,
+    Headers,
+    Fields,
+    All,
+}
+
+/// The format of a delimited file, with builder methods for each option
+///
+/// Defaults to CSV: comma delimited, `"` quoted, with a header row and no trimming.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dialect {
+    delimiter: char,
+    quote: char,
+    has_headers: bool,
+    trim: Trim,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: '"',
+            has_headers: true,
+            trim: Trim::None,
+        }
+    }
+}
+
+impl Dialect {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// tab delimited
+    pub fn tsv() -> Self {
+        Self::default().delimiter('\t')
+    }
+
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn quote(mut self, quote: char) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// whether the first record is a header row, rather than data
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    pub fn trim(mut self, trim: Trim) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// the records after the cursor, read one at a time
+    pub fn records<'a>(&self, c: impl Into<Cursor<'a>>) -> Records<'a> {
+        let c = c.into();
+        let (rest, error) = match c.str() {
+            Ok(s) => (s, None),
+            Err(e) => ("", Some(e)),
+        };
+        Records {
+            dialect: *self,
+            rest,
+            headers: None,
+            error,
+        }
+    }
+
+    /// a record and its line ending, trimmed as for data rows
+    pub fn record<'a>(&self, s: &'a str) -> Result<(&'a str, Record<'a>), ParsingError> {
+        self.parse_record(s, matches!(self.trim, Trim::Fields | Trim::All))
+    }
+
+    /// a single field, with its quotes removed
+    pub fn field<'a>(&self, s: &'a str) -> Result<(&'a str, Cow<'a, str>), ParsingError> {
+        self.parse_field(s, matches!(self.trim, Trim::Fields | Trim::All))
+    }
+
+    /// A lexer matching a record, without its line ending, for use with `select`
+    pub fn lex_record<'a, C: Matchable<'a> + Clone>(&self, c: C) -> C {
+        let c = self.lex_field(c);
+        c.repeat(0.., |c| self.lex_field(c.char(self.delimiter)))
+    }
+
+    fn lex_field<'a, C: Matchable<'a> + Clone>(&self, c: C) -> C {
+        let trim = self.trim != Trim::None;
+        let c = match trim {
+            true => c.chars_in(0.., self.spaces()),
+            false => c,
+        };
+        match c.str() {
+            Ok(s) if s.starts_with(self.quote) => {
+                let c = self.lex_quoted(c.char(self.quote)).char(self.quote);
+                match trim {
+                    true => c.chars_in(0.., self.spaces()),
+                    false => c,
+                }
+            }
+            _ => c.chars_not_in(0.., &[self.delimiter, '\n', '\r']),
+        }
+    }
+
+    // the body of a quoted field, up to the closing quote
+    fn lex_quoted<'a, C: Matchable<'a> + Clone>(&self, c: C) -> C {
+        let q = self.quote;
+        c.repeat(0.., |c| match c.str() {
+            Ok(s) if s.starts_with(q) => c.chars_in(2..=2, &[q]),
+            _ => c.chars_not_in(1.., &[q]),
+        })
+    }
+
+    // spaces and tabs, unless tab is the delimiter
+    fn spaces(&self) -> &'static [char] {
+        match self.delimiter {
+            '\t' => &[' '],
+            _ => &[' ', '\t'],
+        }
+    }
+
+    fn parse_record<'a>(
+        &self,
+        s: &'a str,
+        trim: bool,
+    ) -> Result<(&'a str, Record<'a>), ParsingError> {
+        let (c, first) = Cursor::from(s)
+            .debug_context("record")
+            .parse_with(|s| self.parse_field(s, trim))
+            .validate()?;
+        let mut fields = vec![first];
+        let next_field = |s| {
+            Cursor::from(s)
+                .char(self.delimiter)
+                .parse_with(|s| self.parse_field(s, trim))
+                .validate()
+        };
+        let c = Cursor::from(c)
+            .parse_struct_vec_to(next_field, &mut fields)?
+            .end_of_line()
+            .validate()?;
+        Ok((c, fields))
+    }
+
+    fn parse_field<'a>(
+        &self,
+        s: &'a str,
+        trim: bool,
+    ) -> Result<(&'a str, Cow<'a, str>), ParsingError> {
+        let spaces = self.spaces();
+        let s = match trim {
+            true => s.trim_start_matches(spaces),
+            false => s,
+        };
+        if s.starts_with(self.quote) {
+            let (c, field) = Cursor::from(s)
+                .debug_context("quoted_field")
+                .char(self.quote)
+                .select(|c| self.lex_quoted(c))
+                .parse_selection_with(|body| Ok::<_, ParsingError>(self.unescape(body)))
+                .char(self.quote)
+                .validate()?;
+            let c = match trim {
+                true => c.trim_start_matches(spaces),
+                false => c,
+            };
+            return Ok((c, field));
+        }
+        let (c, field) = Cursor::from(s)
+            .debug_context("field")
+            .chars_not_in(0.., &[self.delimiter, '\n', '\r'])
+            .parse_selection_as_str()
+            .validate()?;
+        let field = match trim {
+            true => field.trim_end_matches(spaces),
+            false => field,
+        };
+        Ok((c, Cow::Borrowed(field)))
+    }
+
+    // the body has been lexed, so any quotes are doubled
+    fn unescape<'a>(&self, body: &'a str) -> Cow<'a, str> {
+        if !body.contains(self.quote) {
+            return Cow::Borrowed(body);
+        }
+        let mut second = false;
+        let unescaped = body.chars().filter(|&ch| {
+            if ch == self.quote {
+                second = !second;
+                return second;
+            }
+            true
+        });
+        Cow::Owned(unescaped.collect())
+    }
+}
+
+/// Iterator over the data records of a file, created by [`Dialect::records`]
+///
+/// Iteration ends after the first error.
+#[derive(Debug, Clone)]
+pub struct Records<'a> {
+    dialect: Dialect,
+    rest: &'a str,
+    headers: Option<Record<'a>>,
+    error: Option<ParsingError>,
+}
+
+impl<'a> Records<'a> {
+    /// the header row, read on first use, or None if the dialect has no headers
+    pub fn headers(&mut self) -> Result<Option<&Record<'a>>, ParsingError> {
+        if self.dialect.has_headers && self.headers.is_none() {
+            let trim = matches!(self.dialect.trim, Trim::Headers | Trim::All);
+            let headers = self.step(|d, s| d.parse_record(s, trim));
+            self.headers = Some(headers.unwrap_or(Ok(vec![]))?);
+        }
+        Ok(self.headers.as_ref())
+    }
+
+    /// the remaining input
+    pub fn rest(&self) -> &'a str {
+        self.rest
+    }
+
+    /// the data rows converted with `FromStr` from the text of each record
+    pub fn rows<T>(mut self) -> impl Iterator<Item = Result<T, ParsingError>> + 'a
+    where
+        T: FromStr + Debug + 'a,
+    {
+        let headers = self.headers().err();
+        headers
+            .map(Err)
+            .into_iter()
+            .chain(std::iter::from_fn(move || {
+                self.step(|d, s| {
+                    Cursor::from(s)
+                        .debug_context("row")
+                        .select(|c| d.lex_record(c))
+                        .parse_selection::<T>()
+                        .end_of_line()
+                        .validate()
+                })
+            }))
+    }
+
+    // parses the next record after any blank lines, ending iteration on error
+    fn step<T, F>(&mut self, parser: F) -> Option<Result<T, ParsingError>>
+    where
+        F: FnOnce(&Dialect, &'a str) -> Result<(&'a str, T), ParsingError>,
+    {
+        if let Some(e) = self.error.take() {
+            self.rest = "";
+            return Some(Err(e));
+        }
+        let s = self.rest.trim_start_matches(['\r', '\n']);
+        if s.is_empty() {
+            self.rest = s;
+            return None;
+        }
+        match parser(&self.dialect, s) {
+            Ok((rest, t)) => {
+                self.rest = rest;
+                Some(Ok(t))
+            }
+            Err(e) => {
+                self.rest = "";
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Result<Record<'a>, ParsingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Err(e) = self.headers() {
+            return Some(Err(e));
+        }
+        self.step(|d, s| d.record(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    fn read(dialect: Dialect, s: &str) -> Result<Vec<Vec<String>>, ParsingError> {
+        dialect
+            .records(s)
+            .map(|r| r.map(|r| r.into_iter().map(Cow::into_owned).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_records() {
+        let csv = Dialect::new().has_headers(false);
+        assert_eq!(
+            read(csv, "a,b,c\n1,,3").unwrap(),
+            [["a", "b", "c"], ["1", "", "3"]]
+        );
+        assert_eq!(
+            read(csv, "a,b\r\n\r\n\nc,d\r\n").unwrap(),
+            [["a", "b"], ["c", "d"]]
+        );
+        assert_eq!(read(csv, "").unwrap(), Vec::<Vec<String>>::new());
+        assert_eq!(read(csv, "x").unwrap(), [["x"]]);
+
+        let quoted = r#""a,b","say ""hi""","line
+break",""
+"#;
+        assert_eq!(
+            read(csv, quoted).unwrap(),
+            [["a,b", "say \"hi\"", "line\nbreak", ""]]
+        );
+
+        // borrowed unless a quote is doubled
+        let (_, rec) = csv.record(r#""plain","q""""#).unwrap();
+        assert!(matches!(rec[0], Cow::Borrowed("plain")));
+        assert!(matches!(rec[1], Cow::Owned(_)));
+        assert_eq!(rec[1], "q\"");
+
+        assert_eq!(read(csv, "\"unterminated\n").is_err(), true);
+        assert_eq!(read(csv, "\"a\"b,c").is_err(), true);
+        let mut records = csv.records("a\n\"b");
+        assert_eq!(records.next().unwrap().unwrap(), ["a"]);
+        assert_eq!(records.next().unwrap().is_err(), true);
+        assert_eq!(records.next().is_none(), true);
+    }
+
+    #[test]
+    fn test_dialects() {
+        let tsv = Dialect::tsv().has_headers(false).trim(Trim::All);
+        assert_eq!(read(tsv, " a \t b\t\n").unwrap(), [["a", "b", ""]]);
+
+        let semi = Dialect::new().delimiter(';').quote('\'').has_headers(false);
+        assert_eq!(
+            read(semi, "'a;b';'it''s';\"x\"").unwrap(),
+            [["a;b", "it's", "\"x\""]]
+        );
+
+        let mut records = Dialect::new()
+            .trim(Trim::Headers)
+            .records(" id , name \n 1 , a \n");
+        assert_eq!(records.headers().unwrap().unwrap(), &["id", "name"]);
+        assert_eq!(records.next().unwrap().unwrap(), [" 1 ", " a "]);
+
+        let trimmed = Dialect::new().has_headers(false).trim(Trim::Fields);
+        assert_eq!(read(trimmed, r#" "a " , b "#).unwrap(), [["a ", "b"]]);
+
+        let mut none = Dialect::new().has_headers(false).records("a\n");
+        assert_eq!(none.headers().unwrap(), None);
+    }
+
+    #[test]
+    fn test_rows() {
+        #[derive(Debug, PartialEq)]
+        struct Row(String, u32);
+
+        impl FromStr for Row {
+            type Err = ParsingError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let (_, f) = Dialect::new().record(s)?;
+                Ok(Row(f[0].to_string(), f[1].parse()?))
+            }
+        }
+
+        let text = "name,age\n\"smith, j\",42\nbloggs,7\n";
+        let rows: Vec<Row> = Dialect::new()
+            .records(text)
+            .rows()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows, [Row("smith, j".into(), 42), Row("bloggs".into(), 7)]);
+
+        let mut rows = Dialect::new().records("n,a\nx,old\n").rows::<Row>();
+        assert_eq!(rows.next().unwrap().is_err(), true);
+
+        // within another parser, selecting just one record
+        let (c, row) = Cursor::from("x,1\ny,2")
+            .select(|c| Dialect::new().lex_record(c))
+            .parse_selection::<Row>()
+            .validate()
+            .unwrap();
+        assert_eq!((c, row), ("\ny,2", Row("x".into(), 1)));
+    }
+
+    #[test]
+    fn test_records_from_cursor() {
+        let c = Cursor::from("# comment\na,b\n").scan_eol();
+        let mut records = Dialect::new().records(c);
+        assert_eq!(records.headers().unwrap().unwrap(), &["a", "b"]);
+        assert_eq!(records.next().is_none(), true);
+        assert_eq!(records.rest(), "");
+    }
+}
@@ -0,0 +1,1069 @@
+//! Dates, times, UTC offsets and durations, in ISO 8601, RFC 3339 and RFC 2822 formats,
+//! Common Log Format timestamps, and times on 12 or 24 hour clocks.
+//!
+//! The parsers are stir-style and return plain structs, with fields range checked (eg
+//! February 29th only in leap years). A failed check is a `ParsingError::Conversion` of
+//! the text matched. The structs implement `FromStr` for their ISO 8601 formats, so can
+//! also be captured with `parse_selection`, and `Display` them in the same format.
+//!
+//! ```
+//! use daisychain::contrib::datetime::{self, Date, DateTime, Duration, Time};
+//! use daisychain::prelude::*;
+//!
+//! let (_, dt) = datetime::rfc3339("2024-02-29T13:45:30.25+01:00").unwrap();
+//! assert_eq!(dt.date, Date::new(2024, 2, 29).unwrap());
+//! assert_eq!(dt.time.nanosecond, 250_000_000);
+//! assert_eq!(dt.to_string(), "2024-02-29T13:45:30.25+01:00");
+//!
+//! let (_, dt) = datetime::rfc2822("Fri, 21 Nov 1997 09:55:06 -0600").unwrap();
+//! assert_eq!(dt.offset.unwrap().seconds, -6 * 3600);
+//!
+//! let (_, time) = datetime::clock("9:05 pm").unwrap();
+//! assert_eq!(time, Time::new(21, 5, 0, 0).unwrap());
+//!
+//! let (c, d) = Cursor::from("took PT1M30.5S")
+//!     .text("took ")
+//!     .non_ws()
+//!     .parse_selection::<Duration>()
+//!     .validate()
+//!     .unwrap();
+//! assert_eq!((d.minutes, d.seconds, d.nanoseconds), (1, 30, 500_000_000));
+//! assert_eq!(datetime::date("2023-02-29").is_err(), true);
+//! ```
+//!
+//! With the `chrono` or `time` cargo features, the structs convert to those crates'
+//! types using `TryFrom`.
+use std::{error::Error, fmt, str::FromStr};
+
+use crate::prelude::*;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateTimeError {
+    /// a field outside its valid range, eg month 13 or the 30th of February
+    OutOfRange { field: &'static str, value: i64 },
+    /// the day of the week given does not match the date
+    Weekday,
+    /// a conversion (with the `chrono` or `time` features) to a type which cannot hold the value
+    Unrepresentable(&'static str),
+    /// a conversion needing an offset from a [`DateTime`] without one
+    MissingOffset,
+    /// a conversion needing a fixed length from a [`Duration`] with years or months
+    NominalDuration,
+}
+
+impl fmt::Display for DateTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::OutOfRange { field, value } => write!(f, "{field} {value} is out of range"),
+            Self::Weekday => write!(f, "day of week does not match the date"),
+            Self::Unrepresentable(type_name) => write!(f, "value cannot be held by {type_name}"),
+            Self::MissingOffset => write!(f, "date and time has no offset"),
+            Self::NominalDuration => write!(f, "duration has years or months"),
+        }
+    }
+}
+
+impl Error for DateTimeError {}
+
+fn out_of_range(field: &'static str, value: impl Into<i64>) -> DateTimeError {
+    DateTimeError::OutOfRange {
+        field,
+        value: value.into(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u8, day: u8) -> Result<Self, DateTimeError> {
+        if !(1..=12).contains(&month) {
+            return Err(out_of_range("month", month));
+        }
+        if day == 0 || day > days_in_month(year, month) {
+            return Err(out_of_range("day", day));
+        }
+        Ok(Self { year, month, day })
+    }
+
+    /// 0 for Monday to 6 for Sunday
+    pub fn weekday(&self) -> u8 {
+        // days since 1970-01-01 (a Thursday), from Howard Hinnant's days_from_civil
+        let (m, d) = (i64::from(self.month), i64::from(self.day));
+        let y = i64::from(self.year) - i64::from(m <= 2);
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + d - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146_097 + doe - 719_468;
+        (days + 3).rem_euclid(7) as u8
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    /// 60 for a leap second
+    pub second: u8,
+    pub nanosecond: u32,
+}
+
+impl Time {
+    pub fn new(hour: u8, minute: u8, second: u8, nanosecond: u32) -> Result<Self, DateTimeError> {
+        if hour > 23 {
+            return Err(out_of_range("hour", hour));
+        }
+        if minute > 59 {
+            return Err(out_of_range("minute", minute));
+        }
+        if second > 60 {
+            return Err(out_of_range("second", second));
+        }
+        if nanosecond >= 1_000_000_000 {
+            return Err(out_of_range("nanosecond", nanosecond));
+        }
+        Ok(Self {
+            hour,
+            minute,
+            second,
+            nanosecond,
+        })
+    }
+}
+
+/// An offset from UTC, positive to the east
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Offset {
+    pub seconds: i32,
+}
+
+impl Offset {
+    pub const UTC: Offset = Offset { seconds: 0 };
+
+    fn new(negative: bool, hours: u8, minutes: u8) -> Result<Self, DateTimeError> {
+        if hours > 23 {
+            return Err(out_of_range("offset hours", hours));
+        }
+        if minutes > 59 {
+            return Err(out_of_range("offset minutes", minutes));
+        }
+        let seconds = i32::from(hours) * 3600 + i32::from(minutes) * 60;
+        Ok(Self {
+            seconds: if negative { -seconds } else { seconds },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateTime {
+    pub date: Date,
+    pub time: Time,
+    /// None for a local time
+    pub offset: Option<Offset>,
+}
+
+/// An ISO 8601 duration. Years and months vary in length, so are kept apart from the rest
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Duration {
+    pub years: u32,
+    pub months: u32,
+    pub weeks: u32,
+    pub days: u32,
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub nanoseconds: u32,
+}
+
+impl Duration {
+    /// the length, as long as there are no years or months
+    pub fn fixed_seconds(&self) -> Result<u64, DateTimeError> {
+        if self.years != 0 || self.months != 0 {
+            return Err(DateTimeError::NominalDuration);
+        }
+        let days = u64::from(self.weeks) * 7 + u64::from(self.days);
+        Ok(
+            ((days * 24 + u64::from(self.hours)) * 60 + u64::from(self.minutes)) * 60
+                + u64::from(self.seconds),
+        )
+    }
+}
+
+impl TryFrom<Duration> for std::time::Duration {
+    type Error = DateTimeError;
+
+    fn try_from(d: Duration) -> Result<Self, Self::Error> {
+        Ok(std::time::Duration::new(d.fixed_seconds()?, d.nanoseconds))
+    }
+}
+
+// the text matched by a parser, with any range error as a conversion error
+fn checked<'a, T>(
+    s: &'a str,
+    c: &'a str,
+    res: Result<T, DateTimeError>,
+) -> Result<(&'a str, T), ParsingError> {
+    match res {
+        Ok(t) => Ok((c, t)),
+        Err(e) => Err(ParsingError::conversion::<T>(&s[..s.len() - c.len()], e)),
+    }
+}
+
+/// `YYYY-MM-DD` or `YYYYMMDD`
+pub fn date(s: &str) -> Result<(&str, Date), ParsingError> {
+    let sep = match s.as_bytes().get(4) {
+        Some(b'-') => "-",
+        _ => "",
+    };
+    let (c, year, month, day) = Cursor::from(s)
+        .debug_context("date")
+        .digits(4..=4)
+        .parse_selection::<i32>()
+        .text(sep)
+        .digits(2..=2)
+        .parse_selection::<u8>()
+        .text(sep)
+        .digits(2..=2)
+        .parse_selection::<u8>()
+        .validate()?;
+    checked(s, c, Date::new(year, month, day))
+}
+
+/// `hh:mm[:ss[.fff]]` or `hhmm[ss[.fff]]`, with the fraction (`.` or `,`) kept to nanoseconds
+pub fn time(s: &str) -> Result<(&str, Time), ParsingError> {
+    let sep = match s.as_bytes().get(2) {
+        Some(b':') => ":",
+        _ => "",
+    };
+    let (c, hour, minute) = Cursor::from(s)
+        .debug_context("time")
+        .digits(2..=2)
+        .parse_selection::<u8>()
+        .text(sep)
+        .digits(2..=2)
+        .parse_selection::<u8>()
+        .validate()?;
+    let (c, second, nanosecond) = match Cursor::from(c)
+        .text(sep)
+        .digits(2..=2)
+        .parse_selection::<u8>()
+        .validate()
+    {
+        Ok((c, second)) => match fraction(c) {
+            Ok((c, nanos)) => (c, second, nanos),
+            Err(_) => (c, second, 0),
+        },
+        Err(_) => (c, 0, 0),
+    };
+    checked(s, c, Time::new(hour, minute, second, nanosecond))
+}
+
+// a decimal fraction as nanoseconds, ignoring any digits beyond
+fn fraction(s: &str) -> Result<(&str, u32), ParsingError> {
+    let (c, digits) = Cursor::from(s)
+        .text_alt(&[".", ","])
+        .digits(1..)
+        .parse_selection_as_str()
+        .validate()?;
+    let nanos = digits
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(9)
+        .fold(0, |n, b| n * 10 + u32::from(b - b'0'));
+    Ok((c, nanos))
+}
+
+/// `Z`, `±hh:mm`, `±hhmm` or `±hh`
+pub fn offset(s: &str) -> Result<(&str, Offset), ParsingError> {
+    if let Some(c) = s.strip_prefix(['Z', 'z']) {
+        return Ok((c, Offset::UTC));
+    }
+    let (c, sign, hours) = Cursor::from(s)
+        .debug_context("offset")
+        .text_alt(&["+", "-"])
+        .parse_selection_as_str()
+        .digits(2..=2)
+        .parse_selection::<u8>()
+        .validate()?;
+    let (c, minutes) = Cursor::from(c)
+        .maybe(":")
+        .digits(2..=2)
+        .parse_selection::<u8>()
+        .validate()
+        .unwrap_or((c, 0));
+    checked(s, c, Offset::new(sign == "-", hours, minutes))
+}
+
+/// an ISO 8601 date and time, with an optional offset, eg `2024-02-29T13:45:30+01:00`
+pub fn datetime(s: &str) -> Result<(&str, DateTime), ParsingError> {
+    let (c, date, time) = Cursor::from(s)
+        .debug_context("datetime")
+        .parse_with(date)
+        .text_alt(&["T", "t"])
+        .parse_with(time)
+        .validate()?;
+    let (c, offset) = match c.starts_with(['Z', 'z', '+', '-']) {
+        true => offset(c).map(|(c, offset)| (c, Some(offset)))?,
+        false => (c, None),
+    };
+    Ok((c, DateTime { date, time, offset }))
+}
+
+/// An RFC 3339 timestamp, which (unlike ISO 8601 generally) must have seconds and
+/// an offset, and may separate the date and time with a space
+pub fn rfc3339(s: &str) -> Result<(&str, DateTime), ParsingError> {
+    let (c, date, time, offset) = Cursor::from(s)
+        .debug_context("rfc3339")
+        .select(|c| {
+            c.digits(4..=4)
+                .text("-")
+                .digits(2..=2)
+                .text("-")
+                .digits(2..=2)
+        })
+        .parse_selection::<Date>()
+        .text_alt(&["T", "t", " "])
+        .select(|c| {
+            let c = c
+                .digits(2..=2)
+                .text(":")
+                .digits(2..=2)
+                .text(":")
+                .digits(2..=2);
+            match c.str() {
+                Ok(s) if s.starts_with('.') => c.text(".").digits(1..),
+                _ => c,
+            }
+        })
+        .parse_selection::<Time>()
+        .select(|c| match c.str() {
+            Ok(s) if s.starts_with(['Z', 'z']) => c.chars_in(1..=1, &['Z', 'z']),
+            _ => c
+                .text_alt(&["+", "-"])
+                .digits(2..=2)
+                .text(":")
+                .digits(2..=2),
+        })
+        .parse_selection::<Offset>()
+        .validate()?;
+    let offset = Some(offset);
+    Ok((c, DateTime { date, time, offset }))
+}
+
+/// An RFC 2822 (email) date, eg `Fri, 21 Nov 1997 09:55:06 -0600`. The day of the week
+/// is optional, but checked against the date. The obsolete zones `UT`, `GMT` and
+/// the US zones (eg `EST`) are accepted, but not comments.
+pub fn rfc2822(s: &str) -> Result<(&str, DateTime), ParsingError> {
+    let (c, weekday) = match Cursor::from(s)
+        .alphabetics(3..=3)
+        .parse_selection_as_str()
+        .hws()
+        .text(",")
+        .ws()
+        .validate()
+    {
+        Ok((c, weekday)) => (c, Some(weekday)),
+        Err(_) => (s, None),
+    };
+    let (c, day, month, year) = Cursor::from(c)
+        .debug_context("rfc2822")
+        .digits(1..=2)
+        .parse_selection::<u8>()
+        .ws()
+        .alphabetics(3..=3)
+        .parse_selection_as_str()
+        .try_map_last(month_number)
+        .ws()
+        .digits(4..=4)
+        .parse_selection::<i32>()
+        .validate()?;
+    let date = checked(s, c, Date::new(year, month, day))?.1;
+    let (c, time, offset) = Cursor::from(c)
+        .ws()
+        .parse_with(time)
+        .ws()
+        .parse_with(zone)
+        .validate()?;
+    if let Some(weekday) = weekday {
+        let expected = WEEKDAYS[usize::from(date.weekday())];
+        if !weekday.eq_ignore_ascii_case(expected) {
+            return checked(s, c, Err(DateTimeError::Weekday));
+        }
+    }
+    let offset = Some(offset);
+    Ok((c, DateTime { date, time, offset }))
+}
+
+/// A Common Log Format timestamp, as written by Apache and Nginx, eg `10/Oct/2000:13:55:36 -0700`
+pub fn clf(s: &str) -> Result<(&str, DateTime), ParsingError> {
+    let (c, day, month, year) = Cursor::from(s)
+        .debug_context("clf")
+        .digits(2..=2)
+        .parse_selection::<u8>()
+        .text("/")
+        .alphabetics(3..=3)
+        .parse_selection_as_str()
+        .try_map_last(month_number)
+        .text("/")
+        .digits(4..=4)
+        .parse_selection::<i32>()
+        .validate()?;
+    let date = checked(s, c, Date::new(year, month, day))?.1;
+    let (c, time, offset) = Cursor::from(c)
+        .text(":")
+        .parse_with(time)
+        .text(" ")
+        .parse_with(offset)
+        .validate()?;
+    let offset = Some(offset);
+    Ok((c, DateTime { date, time, offset }))
+}
+
+// 1 for `Jan`, in any case
+pub(crate) fn month_number(name: &str) -> Result<u8, String> {
+    match MONTHS.iter().position(|m| m.eq_ignore_ascii_case(name)) {
+        Some(i) => Ok(i as u8 + 1),
+        None => Err(format!("unknown month '{name}'")),
+    }
+}
+
+// a numeric zone, or an obsolete name
+fn zone(s: &str) -> Result<(&str, Offset), ParsingError> {
+    const NAMES: [(&str, i32); 11] = [
+        ("UT", 0),
+        ("GMT", 0),
+        ("Z", 0),
+        ("EST", -5),
+        ("EDT", -4),
+        ("CST", -6),
+        ("CDT", -5),
+        ("MST", -7),
+        ("MDT", -6),
+        ("PST", -8),
+        ("PDT", -7),
+    ];
+    if s.starts_with(['+', '-']) {
+        let (c, sign, hours, minutes) = Cursor::from(s)
+            .debug_context("zone")
+            .text_alt(&["+", "-"])
+            .parse_selection_as_str()
+            .digits(2..=2)
+            .parse_selection::<u8>()
+            .digits(2..=2)
+            .parse_selection::<u8>()
+            .validate()?;
+        return checked(s, c, Offset::new(sign == "-", hours, minutes));
+    }
+    let (c, name) = Cursor::from(s)
+        .debug_context("zone")
+        .alphabetics(1..)
+        .parse_selection_as_str()
+        .validate()?;
+    match NAMES.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+        Some((_, hours)) => Ok((
+            c,
+            Offset {
+                seconds: hours * 3600,
+            },
+        )),
+        None => Err(ParsingError::conversion::<Offset>(
+            name,
+            format!("unknown zone '{name}'"),
+        )),
+    }
+}
+
+/// A 12 or 24 hour clock time, `h:mm[:ss]`, with an optional `am`/`pm` (or `a.m.`/`p.m.`)
+/// in either case, eg `9:05 pm`. With am/pm hours run from 1 to 12, and 12 am is midnight.
+pub fn clock(s: &str) -> Result<(&str, Time), ParsingError> {
+    let (c, hour, minute) = Cursor::from(s)
+        .debug_context("clock")
+        .digits(1..=2)
+        .parse_selection::<u8>()
+        .text(":")
+        .digits(2..=2)
+        .parse_selection::<u8>()
+        .validate()?;
+    let (c, second) = Cursor::from(c)
+        .text(":")
+        .digits(2..=2)
+        .parse_selection::<u8>()
+        .validate()
+        .unwrap_or((c, 0));
+    let (c, hour) = match meridiem(c) {
+        Some((c, _)) if hour == 0 || hour > 12 => {
+            return checked(s, c, Err(out_of_range("hour", hour)))
+        }
+        Some((c, pm)) => (c, hour % 12 + if pm { 12 } else { 0 }),
+        None => (c, hour),
+    };
+    checked(s, c, Time::new(hour, minute, second, 0))
+}
+
+// am or pm after optional spaces, as whether it was pm
+fn meridiem(s: &str) -> Option<(&str, bool)> {
+    let t = s.trim_start_matches([' ', '\t']);
+    ["am", "pm", "a.m.", "p.m."].iter().find_map(|m| {
+        let rest = t.get(m.len()..)?;
+        let word_ends = !rest.starts_with(|ch: char| ch.is_alphanumeric());
+        (t[..m.len()].eq_ignore_ascii_case(m) && word_ends).then(|| (rest, m.starts_with('p')))
+    })
+}
+
+/// An ISO 8601 duration `PnYnMnWnDTnHnMnS`, eg `P1Y2M10DT2H30M` or `PT0.5S`, with any
+/// fraction on the seconds only
+pub fn duration(s: &str) -> Result<(&str, Duration), ParsingError> {
+    let mut d = Duration::default();
+    let c = Cursor::from(s)
+        .debug_context("duration")
+        .text("P")
+        .validate()?;
+    let (c, date_units) = units(
+        c,
+        &mut [
+            ('Y', &mut d.years),
+            ('M', &mut d.months),
+            ('W', &mut d.weeks),
+            ('D', &mut d.days),
+        ],
+    );
+    let (c, time_units) = match c.strip_prefix('T') {
+        Some(c) => {
+            let (c, n) = units(c, &mut [('H', &mut d.hours), ('M', &mut d.minutes)]);
+            match seconds(c) {
+                Some((c, seconds, nanos)) => {
+                    (d.seconds, d.nanoseconds) = (seconds, nanos);
+                    (c, n + 1)
+                }
+                None if n == 0 => {
+                    return Err(ParsingError::NoMatch {
+                        action: "duration",
+                        args: "",
+                    })
+                }
+                None => (c, n),
+            }
+        }
+        None => (c, 0),
+    };
+    if date_units + time_units == 0 {
+        return Err(ParsingError::NoMatch {
+            action: "duration",
+            args: "",
+        });
+    }
+    Ok((c, d))
+}
+
+// the integer units present, in order, and how many there were
+fn units<'a>(mut s: &'a str, units: &mut [(char, &mut u32)]) -> (&'a str, usize) {
+    let mut count = 0;
+    for (designator, value) in units {
+        if let Ok((c, n)) = Cursor::from(s)
+            .digits(1..)
+            .parse_selection::<u32>()
+            .char(*designator)
+            .validate()
+        {
+            (s, **value, count) = (c, n, count + 1);
+        }
+    }
+    (s, count)
+}
+
+fn seconds(s: &str) -> Option<(&str, u32, u32)> {
+    let (c, seconds) = Cursor::from(s)
+        .digits(1..)
+        .parse_selection::<u32>()
+        .validate()
+        .ok()?;
+    let (c, nanos) = fraction(c).unwrap_or((c, 0));
+    let c = c.strip_prefix('S')?;
+    Some((c, seconds, nanos))
+}
+
+// all of the text, as for FromStr
+fn all<T>(res: Result<(&str, T), ParsingError>) -> Result<T, ParsingError> {
+    let (c, t) = res?;
+    Cursor::from(c).end_of_stream().validate()?;
+    Ok(t)
+}
+
+impl FromStr for Date {
+    type Err = ParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all(date(s))
+    }
+}
+
+impl FromStr for Time {
+    type Err = ParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all(time(s))
+    }
+}
+
+impl FromStr for Offset {
+    type Err = ParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all(offset(s))
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = ParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all(datetime(s))
+    }
+}
+
+impl FromStr for Duration {
+    type Err = ParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all(duration(s))
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+// a fraction of a second, without trailing zeros
+fn write_nanos(f: &mut fmt::Formatter, nanos: u32) -> fmt::Result {
+    if nanos == 0 {
+        return Ok(());
+    }
+    let digits = format!("{nanos:09}");
+    write!(f, ".{}", digits.trim_end_matches('0'))
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        write_nanos(f, self.nanosecond)
+    }
+}
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.seconds == 0 {
+            return write!(f, "Z");
+        }
+        let sign = if self.seconds < 0 { '-' } else { '+' };
+        let minutes = self.seconds.abs() / 60;
+        write!(f, "{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)?;
+        if let Some(offset) = self.offset {
+            write!(f, "{offset}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "P")?;
+        for (n, designator) in [
+            (self.years, 'Y'),
+            (self.months, 'M'),
+            (self.weeks, 'W'),
+            (self.days, 'D'),
+        ] {
+            if n != 0 {
+                write!(f, "{n}{designator}")?;
+            }
+        }
+        let has_seconds = self.seconds != 0 || self.nanoseconds != 0;
+        if self.hours == 0 && self.minutes == 0 && !has_seconds {
+            // an empty duration still needs a unit
+            return match *self == Duration::default() {
+                true => write!(f, "T0S"),
+                false => Ok(()),
+            };
+        }
+        write!(f, "T")?;
+        for (n, designator) in [(self.hours, 'H'), (self.minutes, 'M')] {
+            if n != 0 {
+                write!(f, "{n}{designator}")?;
+            }
+        }
+        if has_seconds {
+            write!(f, "{}", self.seconds)?;
+            write_nanos(f, self.nanoseconds)?;
+            write!(f, "S")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_conversions {
+    use super::*;
+    use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+
+    impl TryFrom<Date> for NaiveDate {
+        type Error = DateTimeError;
+
+        fn try_from(d: Date) -> Result<Self, Self::Error> {
+            NaiveDate::from_ymd_opt(d.year, d.month.into(), d.day.into())
+                .ok_or(DateTimeError::Unrepresentable("chrono::NaiveDate"))
+        }
+    }
+
+    /// a leap second is held as the 59th second with over a billion nanoseconds
+    impl TryFrom<Time> for NaiveTime {
+        type Error = DateTimeError;
+
+        fn try_from(t: Time) -> Result<Self, Self::Error> {
+            let (second, nano) = match t.second {
+                60 => (59, t.nanosecond + 1_000_000_000),
+                second => (second, t.nanosecond),
+            };
+            NaiveTime::from_hms_nano_opt(t.hour.into(), t.minute.into(), second.into(), nano)
+                .ok_or(DateTimeError::Unrepresentable("chrono::NaiveTime"))
+        }
+    }
+
+    impl TryFrom<Offset> for FixedOffset {
+        type Error = DateTimeError;
+
+        fn try_from(o: Offset) -> Result<Self, Self::Error> {
+            FixedOffset::east_opt(o.seconds)
+                .ok_or(DateTimeError::Unrepresentable("chrono::FixedOffset"))
+        }
+    }
+
+    /// the local date and time, ignoring any offset
+    impl TryFrom<DateTime> for NaiveDateTime {
+        type Error = DateTimeError;
+
+        fn try_from(dt: DateTime) -> Result<Self, Self::Error> {
+            Ok(NaiveDateTime::new(dt.date.try_into()?, dt.time.try_into()?))
+        }
+    }
+
+    impl TryFrom<DateTime> for chrono::DateTime<FixedOffset> {
+        type Error = DateTimeError;
+
+        fn try_from(dt: DateTime) -> Result<Self, Self::Error> {
+            let offset = FixedOffset::try_from(dt.offset.ok_or(DateTimeError::MissingOffset)?)?;
+            NaiveDateTime::try_from(dt)?
+                .and_local_timezone(offset)
+                .single()
+                .ok_or(DateTimeError::Unrepresentable("chrono::DateTime"))
+        }
+    }
+
+    impl TryFrom<Duration> for TimeDelta {
+        type Error = DateTimeError;
+
+        fn try_from(d: Duration) -> Result<Self, Self::Error> {
+            let seconds = i64::try_from(d.fixed_seconds()?);
+            seconds
+                .ok()
+                .and_then(|s| TimeDelta::new(s, d.nanoseconds))
+                .ok_or(DateTimeError::Unrepresentable("chrono::TimeDelta"))
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_conversions {
+    use super::*;
+
+    impl TryFrom<Date> for time::Date {
+        type Error = DateTimeError;
+
+        fn try_from(d: Date) -> Result<Self, Self::Error> {
+            let month = time::Month::try_from(d.month);
+            month
+                .and_then(|m| time::Date::from_calendar_date(d.year, m, d.day))
+                .map_err(|_| DateTimeError::Unrepresentable("time::Date"))
+        }
+    }
+
+    /// fails for a leap second, which `time` does not support
+    impl TryFrom<Time> for time::Time {
+        type Error = DateTimeError;
+
+        fn try_from(t: Time) -> Result<Self, Self::Error> {
+            time::Time::from_hms_nano(t.hour, t.minute, t.second, t.nanosecond)
+                .map_err(|_| DateTimeError::Unrepresentable("time::Time"))
+        }
+    }
+
+    impl TryFrom<Offset> for time::UtcOffset {
+        type Error = DateTimeError;
+
+        fn try_from(o: Offset) -> Result<Self, Self::Error> {
+            time::UtcOffset::from_whole_seconds(o.seconds)
+                .map_err(|_| DateTimeError::Unrepresentable("time::UtcOffset"))
+        }
+    }
+
+    /// the local date and time, ignoring any offset
+    impl TryFrom<DateTime> for time::PrimitiveDateTime {
+        type Error = DateTimeError;
+
+        fn try_from(dt: DateTime) -> Result<Self, Self::Error> {
+            Ok(time::PrimitiveDateTime::new(
+                dt.date.try_into()?,
+                dt.time.try_into()?,
+            ))
+        }
+    }
+
+    impl TryFrom<DateTime> for time::OffsetDateTime {
+        type Error = DateTimeError;
+
+        fn try_from(dt: DateTime) -> Result<Self, Self::Error> {
+            let offset = time::UtcOffset::try_from(dt.offset.ok_or(DateTimeError::MissingOffset)?)?;
+            Ok(time::PrimitiveDateTime::try_from(dt)?.assume_offset(offset))
+        }
+    }
+
+    impl TryFrom<Duration> for time::Duration {
+        type Error = DateTimeError;
+
+        fn try_from(d: Duration) -> Result<Self, Self::Error> {
+            let seconds = i64::try_from(d.fixed_seconds()?)
+                .map_err(|_| DateTimeError::Unrepresentable("time::Duration"))?;
+            Ok(time::Duration::new(seconds, d.nanoseconds as i32))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(
+            date("2024-02-29").unwrap(),
+            ("", Date::new(2024, 2, 29).unwrap())
+        );
+        assert_eq!(
+            date("20240229T").unwrap(),
+            ("T", Date::new(2024, 2, 29).unwrap())
+        );
+        assert_eq!(date("2000-02-29").is_ok(), true);
+        for bad in [
+            "1900-02-29",
+            "2023-13-01",
+            "2023-04-31",
+            "2023-00-10",
+            "2023-1-10",
+            "2023-0110",
+        ] {
+            assert_eq!(date(bad).is_err(), true, "{bad}");
+        }
+        let err = date("2023-02-29").unwrap_err();
+        assert!(err.to_string().contains("text='2023-02-29'"), "{err}");
+
+        // 1970-01-01 was a Thursday, 2000-03-01 a Wednesday
+        assert_eq!(Date::new(1970, 1, 1).unwrap().weekday(), 3);
+        assert_eq!(Date::new(2000, 3, 1).unwrap().weekday(), 2);
+        assert_eq!(Date::new(1969, 12, 28).unwrap().weekday(), 6);
+    }
+
+    #[test]
+    fn test_times() {
+        let t = |h, m, s, ns| Time::new(h, m, s, ns).unwrap();
+        assert_eq!(time("13:45").unwrap(), ("", t(13, 45, 0, 0)));
+        assert_eq!(
+            time("13:45:30,5Z").unwrap(),
+            ("Z", t(13, 45, 30, 500_000_000))
+        );
+        assert_eq!(
+            time("134530.123456789123").unwrap().1,
+            t(13, 45, 30, 123_456_789)
+        );
+        assert_eq!(time("23:59:60").unwrap().1.second, 60);
+        assert_eq!(time("24:00").is_err(), true);
+        assert_eq!(time("12:60").is_err(), true);
+        assert_eq!(time("1:30").is_err(), true);
+
+        assert_eq!(offset("+05:30").unwrap().1.seconds, 19_800);
+        assert_eq!(offset("-0800").unwrap().1.seconds, -28_800);
+        assert_eq!(offset("+01").unwrap().1.seconds, 3600);
+        assert_eq!(offset("z").unwrap().1, Offset::UTC);
+        assert_eq!(offset("+24:00").is_err(), true);
+    }
+
+    #[test]
+    fn test_datetimes() {
+        let dt: DateTime = "2024-02-29T13:45:30.25+01:00".parse().unwrap();
+        assert_eq!(dt.to_string(), "2024-02-29T13:45:30.25+01:00");
+        let dt: DateTime = "20240229T134530".parse().unwrap();
+        assert_eq!(
+            (dt.offset, dt.to_string()),
+            (None, "2024-02-29T13:45:30".into())
+        );
+        assert_eq!("2024-02-29T13:45+25:00".parse::<DateTime>().is_err(), true);
+
+        let (_, dt) = rfc3339("1985-04-12 23:20:50.52Z").unwrap();
+        assert_eq!(dt.to_string(), "1985-04-12T23:20:50.52Z");
+        assert_eq!(
+            rfc3339("1996-12-19T16:39:57-08:00")
+                .unwrap()
+                .1
+                .offset
+                .unwrap()
+                .seconds,
+            -28_800
+        );
+        for bad in [
+            "1985-04-12T23:20:50",
+            "1985-04-12T23:20Z",
+            "19850412T232050Z",
+            "1985-04-12T23:20:50+0100",
+        ] {
+            assert_eq!(rfc3339(bad).is_err(), true, "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_rfc2822() {
+        let (c, dt) = rfc2822("Fri, 21 Nov 1997 09:55:06 -0600 rest").unwrap();
+        assert_eq!(c, " rest");
+        assert_eq!(dt.to_string(), "1997-11-21T09:55:06-06:00");
+        let (_, dt) = rfc2822("1 jan 2000 00:00 GMT").unwrap();
+        assert_eq!(dt.to_string(), "2000-01-01T00:00:00Z");
+        assert_eq!(
+            rfc2822("2 Feb 2010 10:00 EST")
+                .unwrap()
+                .1
+                .offset
+                .unwrap()
+                .seconds,
+            -5 * 3600
+        );
+
+        let err = rfc2822("Sat, 21 Nov 1997 09:55:06 -0600").unwrap_err();
+        assert!(err.to_string().contains("day of week"), "{err}");
+        assert_eq!(rfc2822("21 Foo 1997 09:55:06 -0600").is_err(), true);
+        assert_eq!(rfc2822("31 Apr 1997 09:55:06 -0600").is_err(), true);
+        assert_eq!(rfc2822("21 Nov 1997 09:55:06 XYZ").is_err(), true);
+
+        let (c, dt) = clf("10/Oct/2000:13:55:36 -0700] ").unwrap();
+        assert_eq!((c, dt.to_string()), ("] ", "2000-10-10T13:55:36-07:00".to_string()));
+        assert_eq!(clf("31/Sep/2000:13:55:36 -0700").is_err(), true);
+        assert_eq!(clf("10/Oct/2000 13:55:36 -0700").is_err(), true);
+    }
+
+    #[test]
+    fn test_clock() {
+        let t = |h, m, s| Time::new(h, m, s, 0).unwrap();
+        assert_eq!(clock("9:05 pm").unwrap(), ("", t(21, 5, 0)));
+        assert_eq!(clock("12:00AM").unwrap().1, t(0, 0, 0));
+        assert_eq!(clock("12:30 p.m. later").unwrap(), (" later", t(12, 30, 0)));
+        assert_eq!(clock("23:59:59").unwrap().1, t(23, 59, 59));
+        assert_eq!(clock("11:35 AMBER").unwrap(), (" AMBER", t(11, 35, 0)));
+        assert_eq!(clock("13:00 pm").is_err(), true);
+        assert_eq!(clock("0:10 am").is_err(), true);
+        assert_eq!(clock("24:00").is_err(), true);
+    }
+
+    #[test]
+    fn test_durations() {
+        let d: Duration = "P1Y2M3W4DT5H6M7.5S".parse().unwrap();
+        assert_eq!(
+            (
+                d.years,
+                d.months,
+                d.weeks,
+                d.days,
+                d.hours,
+                d.minutes,
+                d.seconds,
+                d.nanoseconds
+            ),
+            (1, 2, 3, 4, 5, 6, 7, 500_000_000)
+        );
+        assert_eq!(d.to_string(), "P1Y2M3W4DT5H6M7.5S");
+        assert_eq!(d.fixed_seconds(), Err(DateTimeError::NominalDuration));
+
+        let d: Duration = "PT1M30S".parse().unwrap();
+        assert_eq!(std::time::Duration::try_from(d).unwrap().as_secs(), 90);
+        assert_eq!(
+            "P2D".parse::<Duration>().unwrap().fixed_seconds(),
+            Ok(2 * 86_400)
+        );
+        assert_eq!(Duration::default().to_string(), "PT0S");
+        assert_eq!("PT0S".parse::<Duration>().unwrap(), Duration::default());
+        for bad in ["P", "PT", "P1H", "P1DT", "PT1.5M", "1D", "P1D2Y"] {
+            assert_eq!(bad.parse::<Duration>().is_err(), true, "{bad}");
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono() {
+        use chrono::{Offset as _, Timelike};
+
+        let dt: DateTime = "2016-12-31T23:59:60.5+01:00".parse().unwrap();
+        let chrono_dt = chrono::DateTime::<chrono::FixedOffset>::try_from(dt).unwrap();
+        // the leap second is held in the nanoseconds
+        assert_eq!((chrono_dt.second(), chrono_dt.nanosecond()), (59, 1_500_000_000));
+        assert_eq!(chrono_dt.offset().fix().local_minus_utc(), 3600);
+        let local: DateTime = "2016-12-31T23:59:00".parse().unwrap();
+        assert_eq!(
+            chrono::DateTime::<chrono::FixedOffset>::try_from(local),
+            Err(DateTimeError::MissingOffset)
+        );
+        let d: Duration = "P1DT1S".parse().unwrap();
+        assert_eq!(
+            chrono::TimeDelta::try_from(d).unwrap().num_seconds(),
+            86_401
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time() {
+        let dt: DateTime = "2024-02-29T13:45:30.25+01:00".parse().unwrap();
+        let odt = time::OffsetDateTime::try_from(dt).unwrap();
+        assert_eq!(odt.unix_timestamp(), 1_709_210_730);
+        let leap: Time = "23:59:60".parse().unwrap();
+        assert_eq!(time::Time::try_from(leap).is_err(), true);
+        let d: Duration = "PT1.5S".parse().unwrap();
+        assert_eq!(time::Duration::try_from(d).unwrap().as_seconds_f64(), 1.5);
+    }
+}
@@ -0,0 +1,216 @@
+//! Email addresses, following the `addr-spec` of [RFC 5322](https://www.rfc-editor.org/rfc/rfc5322#section-3.4.1).
+//!
+//! - the local part is a dot-atom such as `first.last+tag`, or a quoted string such as
+//!   `"john smith"` with `\` escapes, of at most 64 bytes
+//! - the domain is a dot-separated host name with at least two labels, whose last label is
+//!   letters or an `xn--` punycode name, or a domain literal such as `[192.0.2.1]`
+//!
+//! Obsolete syntax, comments and folding whitespace are not accepted, and neither is a
+//! single-label domain such as `root@localhost`.
+//!
+//! ```
+//! use daisychain::contrib::email;
+//! use daisychain::prelude::*;
+//!
+//! let (_, addr) = email::address("first.last+tag@mail.example.com").unwrap();
+//! assert_eq!((addr.local_part, addr.domain), ("first.last+tag", "mail.example.com"));
+//!
+//! let (_, to) = Cursor::from("To: <\"john smith\"@example.org>")
+//!     .find("<")
+//!     .text("<")
+//!     .select(email::lex_address)
+//!     .parse_selection_as_str()
+//!     .text(">")
+//!     .validate()
+//!     .unwrap();
+//! assert_eq!(to, "\"john smith\"@example.org");
+//! ```
+use std::fmt;
+
+use crate::prelude::*;
+use crate::text_parser::apply;
+
+/// An address, borrowed from the input. A quoted local part keeps its quotes and escapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address<'a> {
+    pub local_part: &'a str,
+    pub domain: &'a str,
+}
+
+impl fmt::Display for Address<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}@{}", self.local_part, self.domain)
+    }
+}
+
+/// An address, as a stir-style parser
+pub fn address(s: &str) -> Result<(&str, Address<'_>), ParsingError> {
+    let (c, local_part, domain) = Cursor::from(s)
+        .debug_context("email")
+        .select(local_part)
+        .parse_selection_as_str()
+        .verify_last(|local| local.len() <= 64)
+        .text("@")
+        .select(domain)
+        .parse_selection_as_str()
+        .try_map_last(check_domain)
+        .validate()?;
+    Ok((c, Address { local_part, domain }))
+}
+
+/// Selects an address, with the same rules as [`address`]
+pub fn lex_address<'a, C: Matchable<'a>>(c: C) -> C {
+    apply(c, |s| address(s).ok().map(|(rest, _)| rest), "email", "")
+}
+
+fn local_part<'a, C: Matchable<'a> + Clone>(c: C) -> C {
+    match c.str() {
+        Ok(s) if s.starts_with('"') => c
+            .text("\"")
+            .repeat(0.., |c| match c.str() {
+                Ok(s) if s.starts_with('\\') => c.text("\\").chars_match(1..=1, is_quoted_pair),
+                _ => c.chars_match(1.., is_qtext),
+            })
+            .text("\""),
+        _ => dot_atom(c, is_atext),
+    }
+}
+
+fn domain<'a, C: Matchable<'a> + Clone>(c: C) -> C {
+    match c.str() {
+        Ok(s) if s.starts_with('[') => c.text("[").chars_match(0.., is_dtext).text("]"),
+        _ => dot_atom(c, |ch| ch.is_ascii_alphanumeric() || ch == '-'),
+    }
+}
+
+// one or more runs of `pred`, separated by single dots
+fn dot_atom<'a, C: Matchable<'a> + Clone>(c: C, pred: fn(char) -> bool) -> C {
+    c.chars_match(1.., pred)
+        .repeat(0.., |c| c.text(".").chars_match(1.., pred))
+}
+
+fn check_domain(domain: &str) -> Result<&str, String> {
+    if domain.starts_with('[') {
+        return Ok(domain);
+    }
+    let labels: Vec<_> = domain.split('.').collect();
+    let tld = labels[labels.len() - 1];
+    if domain.len() > 253 || labels.len() < 2 {
+        Err(format!("'{domain}' is not a fully qualified domain"))
+    } else if let Some(label) = labels
+        .iter()
+        .find(|l| l.len() > 63 || l.starts_with('-') || l.ends_with('-'))
+    {
+        Err(format!("invalid domain label '{label}'"))
+    } else if tld.len() < 2
+        || !(tld.starts_with("xn--") || tld.chars().all(|ch| ch.is_ascii_alphabetic()))
+    {
+        Err(format!("invalid top level domain '{tld}'"))
+    } else {
+        Ok(domain)
+    }
+}
+
+fn is_atext(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(ch)
+}
+
+// printable ASCII other than `"` and `\`
+fn is_qtext(ch: char) -> bool {
+    ch == ' ' || ch == '!' || ('#'..='[').contains(&ch) || (']'..='~').contains(&ch)
+}
+
+fn is_quoted_pair(ch: char) -> bool {
+    ch == ' ' || ch == '\t' || ch.is_ascii_graphic()
+}
+
+// printable ASCII other than `[`, `]` and `\`
+fn is_dtext(ch: char) -> bool {
+    ('!'..='Z').contains(&ch) || ('^'..='~').contains(&ch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn test_address() {
+        for s in [
+            "andy@google.com",
+            "first.last+tag@mail.example.co.uk",
+            "x@a.io",
+            "o'brien@example.ie",
+            "{weird}/=?^@example.com",
+            "\"john smith\"@example.org",
+            "\"a\\\"b\\\\c\"@example.org",
+            "\"\"@example.org",
+            "user@[192.0.2.1]",
+            "user@[IPv6:2001:db8::1]",
+            "user@xn--bcher-kva.xn--p1ai",
+            "user@sub-domain.example.com",
+        ] {
+            let (rest, addr) = address(s).unwrap_or_else(|e| panic!("{s}: {e}"));
+            assert_eq!((rest, addr.to_string()), ("", s.to_string()));
+        }
+        let (_, addr) = address("\"john smith\"@example.org").unwrap();
+        assert_eq!(addr.local_part, "\"john smith\"");
+
+        for s in [
+            "google.com",
+            "@example.com",
+            "user@",
+            "user@localhost",
+            ".user@example.com",
+            "user.@example.com",
+            "us..er@example.com",
+            "us er@example.com",
+            "\"unterminated@example.com",
+            "user@example.c",
+            "user@example.123",
+            "user@-example.com",
+            "user@example-.com",
+            "user@[1.2.3.4",
+            "a(b)@example.com",
+        ] {
+            assert_eq!(address(s).map(|(rest, _)| rest).ok(), None, "{s}");
+        }
+        assert_eq!(
+            address(&format!("{}@example.com", "a".repeat(65))).is_err(),
+            true
+        );
+        assert_eq!(address(&format!("a@{}.com", "b".repeat(64))).is_err(), true);
+        assert_eq!(
+            address(&format!("{}@example.com", "a".repeat(64))).is_ok(),
+            true
+        );
+    }
+
+    #[test]
+    fn test_lex_address() {
+        let extent = |s: &'static str| {
+            let rest = lex_address(Cursor::from(s)).str().ok()?;
+            Some(&s[..s.len() - rest.len()])
+        };
+        assert_eq!(extent("andy@google.com"), Some("andy@google.com"));
+        assert_eq!(extent("andy@google.com."), Some("andy@google.com"));
+        assert_eq!(
+            extent("andy@google.com, bea@x.org"),
+            Some("andy@google.com")
+        );
+        assert_eq!(extent("andy@google.com>"), Some("andy@google.com"));
+        assert_eq!(extent("google.com"), None);
+
+        let text = "cc: andy@google.com, bea@example.org";
+        let (_, list) = Cursor::from(text)
+            .text("cc: ")
+            .parse_struct_vec(|s| {
+                let (rest, addr) = address(s)?;
+                Ok((Cursor::from(rest).maybe(",").hws().validate()?, addr))
+            })
+            .validate()
+            .unwrap();
+        let domains: Vec<_> = list.iter().map(|a| a.domain).collect();
+        assert_eq!(domains, ["google.com", "example.org"]);
+    }
+}
@@ -0,0 +1,307 @@
+//! INI and simple TOML-like configuration files.
+//!
+//! - `[section]` headers, with keys before the first header in the unnamed section ""
+//! - `key = value` or `key: value` entries, read line by line
+//! - `;` and `#` comments, on their own line or after a value (preceded by whitespace)
+//! - `"quoted"` values with `\"`, `\\`, `\n`, `\t` and `\r` escapes, and `'literal'` values
+//! - unquoted values continued onto the next line by a trailing `\`, joined with a space
+//!
+//! Values are borrowed from the input unless escaped or continued. Sections appearing
+//! more than once are merged, and the last of any duplicate keys is used by `get`.
+//!
+//! ```
+//! use daisychain::contrib::ini;
+//!
+//! let text = r#"
+//! name = daisychain  ; the crate
+//!
+//! [server]
+//! host: "localhost"
+//! paths = /a, \
+//!         /b
+//! "#;
+//! let config = ini::parse(text).unwrap();
+//! assert_eq!(config.get("", "name"), Some("daisychain"));
+//! assert_eq!(config.get("server", "host"), Some("localhost"));
+//! assert_eq!(config.get("server", "paths"), Some("/a, /b"));
+//!
+//! let err = ini::parse("[server]\nhost = \"localhost\n").unwrap_err();
+//! assert_eq!(err.line, 2);
+//! ```
+use std::borrow::Cow;
+
+use super::unescape;
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ini<'a> {
+    /// in order of first appearance, starting with the unnamed section
+    pub sections: Vec<Section<'a>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Section<'a> {
+    pub name: &'a str,
+    /// in document order, including any duplicate keys
+    pub entries: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+impl<'a> Ini<'a> {
+    pub fn section(&self, name: &str) -> Option<&Section<'a>> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.section(section)?.get(key)
+    }
+}
+
+impl<'a> Section<'a> {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_ref())
+    }
+}
+
+/// A line of a file which could not be parsed, numbered from 1
+pub type IniError = super::LineError;
+
+enum Line<'a> {
+    Blank,
+    Section(&'a str),
+    Entry(&'a str, Cow<'a, str>),
+}
+
+/// Parses a whole file
+pub fn parse(s: &str) -> Result<Ini<'_>, IniError> {
+    let mut sections = vec![Section::default()];
+    let mut current = 0;
+    let mut rest = s;
+    let mut line = 1;
+    while !rest.is_empty() {
+        let (next, item) = line_item(rest).map_err(|source| IniError {
+            line,
+            text: rest.lines().next().unwrap_or_default().to_string(),
+            source,
+        })?;
+        line += rest[..rest.len() - next.len()].matches('\n').count();
+        rest = next;
+        match item {
+            Line::Blank => {}
+            Line::Section(name) => {
+                current = match sections.iter().position(|s| s.name == name) {
+                    Some(i) => i,
+                    None => {
+                        sections.push(Section {
+                            name,
+                            entries: vec![],
+                        });
+                        sections.len() - 1
+                    }
+                }
+            }
+            Line::Entry(key, value) => sections[current].entries.push((key, value)),
+        }
+    }
+    Ok(Ini { sections })
+}
+
+// a line, or several if the value is continued
+fn line_item(s: &str) -> Result<(&str, Line<'_>), ParsingError> {
+    let c = Cursor::from(s).hws();
+    match c.str()?.chars().next() {
+        None | Some('\n' | '\r') => Ok((c.end_of_line().validate()?, Line::Blank)),
+        Some(';' | '#') => Ok((c.scan_eol().validate()?, Line::Blank)),
+        Some('[') => section(c.str()?).map(|(c, name)| (c, Line::Section(name))),
+        _ => entry(c.str()?).map(|(c, (k, v))| (c, Line::Entry(k, v))),
+    }
+}
+
+fn section(s: &str) -> Result<(&str, &str), ParsingError> {
+    let (c, name) = Cursor::from(s)
+        .debug_context("section")
+        .text("[")
+        .hws()
+        .chars_not_in(1.., &[']', '\n', '\r'])
+        .parse_selection_as_str()
+        .text("]")
+        .select(line_end)
+        .validate()?;
+    Ok((c, name.trim_end()))
+}
+
+fn entry(s: &str) -> Result<(&str, (&str, Cow<'_, str>)), ParsingError> {
+    let (c, key, value) = Cursor::from(s)
+        .debug_context("entry")
+        .chars_not_in(1.., &['=', ':', '\n', '\r'])
+        .parse_selection_as_str()
+        .text_alt(&["=", ":"])
+        .hws()
+        .parse_with(value)
+        .validate()?;
+    Ok((c, (key.trim_end(), value)))
+}
+
+/// a value, including the remainder of its line
+pub fn value(s: &str) -> Result<(&str, Cow<'_, str>), ParsingError> {
+    match s.chars().next() {
+        Some('"') => Cursor::from(s)
+            .debug_context("quoted")
+            .text("\"")
+            .select(quoted_body)
+            .parse_selection_with(unescape)
+            .text("\"")
+            .select(line_end)
+            .validate(),
+        Some('\'') => Cursor::from(s)
+            .debug_context("literal")
+            .text("'")
+            .chars_not_in(0.., &['\'', '\n', '\r'])
+            .parse_selection_as_str()
+            .text("'")
+            .select(line_end)
+            .validate()
+            .map(|(c, v)| (c, Cow::Borrowed(v))),
+        _ => unquoted(s),
+    }
+}
+
+// trailing whitespace and any comment, up to and including the line ending
+fn line_end<'a, C: Matchable<'a>>(c: C) -> C {
+    let c = c.hws();
+    match c.str() {
+        Ok(s) if s.starts_with([';', '#']) => c.scan_eol(),
+        _ => c.end_of_line(),
+    }
+}
+
+fn quoted_body<'a, C: Matchable<'a> + Clone>(c: C) -> C {
+    c.repeat(0.., |c| match c.str() {
+        Ok(s) if s.starts_with('\\') => c.text("\\").chars_in(1..=1, &['"', '\\', 'n', 't', 'r']),
+        _ => c.chars_not_in(1.., &['"', '\\', '\n', '\r']),
+    })
+}
+
+fn unquoted(s: &str) -> Result<(&str, Cow<'_, str>), ParsingError> {
+    let mut value = Cow::Borrowed("");
+    let mut rest = s;
+    loop {
+        let (next, text) = Cursor::from(rest)
+            .debug_context("unquoted")
+            .chars_not_in(0.., &['\n', '\r'])
+            .parse_selection_as_str()
+            .end_of_line()
+            .validate()?;
+        let text = strip_comment(text).trim_end();
+        match text.strip_suffix('\\') {
+            Some(part) if !next.is_empty() => {
+                let value = value.to_mut();
+                value.push_str(part.trim_end());
+                value.push(' ');
+                rest = Cursor::from(next).hws().validate()?;
+            }
+            _ if value.is_empty() => return Ok((next, Cow::Borrowed(text))),
+            _ => {
+                value.to_mut().push_str(text);
+                return Ok((next, value));
+            }
+        }
+    }
+}
+
+// a comment must follow whitespace, so that eg `url = a.com/#top` is kept
+fn strip_comment(s: &str) -> &str {
+    let mut after_ws = true;
+    for (i, ch) in s.char_indices() {
+        if after_ws && (ch == ';' || ch == '#') {
+            return &s[..i];
+        }
+        after_ws = ch.is_whitespace();
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+    use test_log::test;
+
+    #[test]
+    fn test_sections() {
+        let text = "\
+# global settings
+debug = true
+
+[ database ]  ; primary
+url = postgres://db/#main
+port: 5432
+
+[cache]
+size=
+
+[database]
+port = 5433
+";
+        let ini = parse(text).unwrap();
+        let names: Vec<_> = ini.sections.iter().map(|s| s.name).collect();
+        assert_eq!(names, ["", "database", "cache"]);
+        assert_eq!(ini.get("", "debug"), Some("true"));
+        assert_eq!(ini.get("database", "url"), Some("postgres://db/#main"));
+        assert_eq!(ini.get("database", "port"), Some("5433"));
+        assert_eq!(ini.section("database").unwrap().entries.len(), 3);
+        assert_eq!(ini.get("cache", "size"), Some(""));
+        assert_eq!(ini.get("cache", "missing"), None);
+        assert_eq!(ini.get("missing", "size"), None);
+        assert_eq!(
+            parse("").unwrap(),
+            Ini {
+                sections: vec![Section::default()]
+            }
+        );
+    }
+
+    #[test]
+    fn test_values() {
+        let text = r#"
+quoted = "a \"b\"\t;c" ; comment
+literal = 'C:\path' # comment
+hash = a#b
+empty_quoted = ""
+list = one, \
+       two,\
+  three
+"#;
+        let ini = parse(text).unwrap();
+        assert_eq!(ini.get("", "quoted"), Some("a \"b\"\t;c"));
+        assert_eq!(ini.get("", "literal"), Some(r"C:\path"));
+        assert_eq!(ini.get("", "hash"), Some("a#b"));
+        assert_eq!(ini.get("", "empty_quoted"), Some(""));
+        assert_eq!(ini.get("", "list"), Some("one, two, three"));
+
+        // borrowed unless escaped or continued
+        let section = &ini.sections[0];
+        assert!(matches!(section.entries[1].1, Cow::Borrowed(_)));
+        assert!(matches!(section.entries[0].1, Cow::Owned(_)));
+
+        let ini = parse("a = x\r\nb = \"y\"\r\n").unwrap();
+        assert_eq!((ini.get("", "a"), ini.get("", "b")), (Some("x"), Some("y")));
+    }
+
+    #[test]
+    fn test_errors() {
+        let line = |s| parse(s).unwrap_err().line;
+        assert_eq!(line("[open\nkey = 1\n"), 1);
+        assert_eq!(line("a = 1\n\n[s]\nno equals here\n"), 4);
+        assert_eq!(line("a = \\\n  b\nc = \"x\" y\n"), 3);
+        assert_eq!(line("a = 'unterminated\n"), 1);
+        assert_eq!(line("= value\n"), 1);
+
+        let err = parse("a = 1\nb = \"\\q\"\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: cannot parse 'b = \"\\q\"'");
+        assert_eq!(err.source().is_some(), true);
+    }
+}
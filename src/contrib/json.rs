@@ -0,0 +1,736 @@
+//! A JSON parser following [RFC 8259](https://www.rfc-editor.org/rfc/rfc8259).
+//!
+//! [`parse`] returns a [`Value`] tree borrowing from the input, with strings only
+//! allocated where they contain escapes. [`events`] streams the same document as
+//! a sequence of [`Event`]s, without building a tree or recursing.
+//!
+//! ```
+//! use daisychain::contrib::json::{self, Event, Value};
+//!
+//! let doc = json::parse(r#"{"name": "daisy\nchain", "tags": [1, -2.5e3, true, null]}"#).unwrap();
+//! assert_eq!(doc.get("name").and_then(Value::as_str), Some("daisy\nchain"));
+//! assert_eq!(doc["tags"][1].as_f64(), Some(-2500.0));
+//! assert_eq!(doc.to_string(), r#"{"name":"daisy\nchain","tags":[1,-2.5e3,true,null]}"#);
+//!
+//! let events: Vec<_> = json::events("[1, {}]").collect::<Result<_, _>>().unwrap();
+//! assert_eq!(events.len(), 5);
+//! assert_eq!(events[0], Event::StartArray);
+//! ```
+//!
+//! The individual productions ([`value`], [`string`], [`number`]) are stir-style
+//! parsers, so can be used within other parsers with `parse_with`.
+use std::{borrow::Cow, fmt, ops::Index};
+
+use crate::prelude::*;
+
+/// JSON whitespace, which unlike `ws()` excludes eg form feed and non-breaking space
+const WS: &[char] = &[' ', '\t', '\n', '\r'];
+const ONE_TO_NINE: &[char] = &['1', '2', '3', '4', '5', '6', '7', '8', '9'];
+const ESCAPES: &[char] = &['"', '\\', '/', 'b', 'f', 'n', 'r', 't'];
+
+/// An array uses one nested parser per level and an object two, so this allows
+/// 100 levels of objects, while keeping well within a 2MB stack in debug builds
+const MAX_DEPTH: usize = 200;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<'a> {
+    Null,
+    Bool(bool),
+    Number(Number<'a>),
+    String(Cow<'a, str>),
+    Array(Vec<Value<'a>>),
+    /// members in document order, including any duplicate keys
+    Object(Vec<(Cow<'a, str>, Value<'a>)>),
+}
+
+/// A number, kept as its text so that no precision is lost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Number<'a>(&'a str);
+
+impl<'a> Number<'a> {
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    /// the nearest f64, which is infinite for numbers beyond its range
+    pub fn as_f64(&self) -> f64 {
+        self.0.parse().unwrap_or(f64::NAN)
+    }
+
+    /// the value, if it is an integer in range
+    pub fn as_i64(&self) -> Option<i64> {
+        self.0.parse().ok()
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.0.parse().ok()
+    }
+}
+
+impl fmt::Display for Number<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl<'a> Value<'a> {
+    /// the value of an object member. With duplicate keys the last is used
+    pub fn get(&self, key: &str) -> Option<&Value<'a>> {
+        self.as_object()?
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<Number<'a>> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(|n| n.as_f64())
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(|n| n.as_i64())
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value<'a>]> {
+        match self {
+            Value::Array(vec) => Some(vec),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(Cow<'a, str>, Value<'a>)]> {
+        match self {
+            Value::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+}
+
+/// `value["key"]`, giving `Null` for a missing member or a value that is not an object
+impl<'a> Index<&str> for Value<'a> {
+    type Output = Value<'a>;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        self.get(key).unwrap_or(&Value::Null)
+    }
+}
+
+/// `value[i]`, giving `Null` when out of range or not an array
+impl<'a> Index<usize> for Value<'a> {
+    type Output = Value<'a>;
+
+    fn index(&self, i: usize) -> &Self::Output {
+        self.as_array()
+            .and_then(|a| a.get(i))
+            .unwrap_or(&Value::Null)
+    }
+}
+
+/// compact JSON text
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Number(n) => write!(f, "{n}"),
+            Value::String(s) => write_string(f, s),
+            Value::Array(vec) => {
+                f.write_str("[")?;
+                for (i, v) in vec.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{v}")?;
+                }
+                f.write_str("]")
+            }
+            Value::Object(members) => {
+                f.write_str("{")?;
+                for (i, (k, v)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_string(f, k)?;
+                    write!(f, ":{v}")?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for ch in s.chars() {
+        match ch {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            '\u{8}' => f.write_str("\\b")?,
+            '\u{c}' => f.write_str("\\f")?,
+            ch if ch < ' ' => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => write!(f, "{ch}")?,
+        }
+    }
+    f.write_str("\"")
+}
+
+/// Parses a complete JSON document, with optional surrounding whitespace.
+///
+/// Nesting is limited (see [`Limits`]), so that hostile input fails rather than
+/// overflowing the stack.
+pub fn parse(s: &str) -> Result<Value<'_>, ParsingError> {
+    let document = |s| Cursor::from(s).parse_with(value).end_of_stream().validate();
+    Limits::new()
+        .max_depth(MAX_DEPTH)
+        .parse(s, document)
+        .map(|(_, v)| v)
+}
+
+/// a value with any surrounding whitespace
+pub fn value(s: &str) -> Result<(&str, Value<'_>), ParsingError> {
+    let s = s.trim_start_matches(WS);
+    let (c, value) = match s.as_bytes().first() {
+        Some(b'{') => object(s).map(|(c, members)| (c, Value::Object(members)))?,
+        Some(b'[') => array(s).map(|(c, vec)| (c, Value::Array(vec)))?,
+        _ => scalar(s)?,
+    };
+    Ok((c.trim_start_matches(WS), value))
+}
+
+// a string, number, true, false or null
+fn scalar(s: &str) -> Result<(&str, Value<'_>), ParsingError> {
+    match s.as_bytes().first() {
+        Some(b'"') => string(s).map(|(c, s)| (c, Value::String(s))),
+        Some(b't') => Cursor::from(s)
+            .text("true")
+            .validate()
+            .map(|c| (c, Value::Bool(true))),
+        Some(b'f') => Cursor::from(s)
+            .text("false")
+            .validate()
+            .map(|c| (c, Value::Bool(false))),
+        Some(b'n') => Cursor::from(s)
+            .text("null")
+            .validate()
+            .map(|c| (c, Value::Null)),
+        _ => number(s).map(|(c, n)| (c, Value::Number(n))),
+    }
+}
+
+fn array(s: &str) -> Result<(&str, Vec<Value<'_>>), ParsingError> {
+    if let Ok(c) = Cursor::from(s)
+        .text("[")
+        .chars_in(0.., WS)
+        .text("]")
+        .validate()
+    {
+        return Ok((c, vec![]));
+    }
+    let (c, first) = Cursor::from(s)
+        .debug_context("array")
+        .text("[")
+        .parse_with(value)
+        .validate()?;
+    let mut values = vec![first];
+    let c = Cursor::from(c)
+        .parse_struct_vec_to(next_value, &mut values)?
+        .text("]")
+        .validate()?;
+    Ok((c, values))
+}
+
+fn next_value(s: &str) -> Result<(&str, Value<'_>), ParsingError> {
+    Cursor::from(s).text(",").parse_with(value).validate()
+}
+
+type Member<'a> = (Cow<'a, str>, Value<'a>);
+
+fn object(s: &str) -> Result<(&str, Vec<Member<'_>>), ParsingError> {
+    if let Ok(c) = Cursor::from(s)
+        .text("{")
+        .chars_in(0.., WS)
+        .text("}")
+        .validate()
+    {
+        return Ok((c, vec![]));
+    }
+    let (c, first) = Cursor::from(s)
+        .debug_context("object")
+        .text("{")
+        .parse_with(member)
+        .validate()?;
+    let mut members = vec![first];
+    let c = Cursor::from(c)
+        .parse_struct_vec_to(next_member, &mut members)?
+        .text("}")
+        .validate()?;
+    Ok((c, members))
+}
+
+fn member(s: &str) -> Result<(&str, Member<'_>), ParsingError> {
+    let (c, key, value) = Cursor::from(s)
+        .debug_context("member")
+        .chars_in(0.., WS)
+        .parse_with(string)
+        .chars_in(0.., WS)
+        .text(":")
+        .parse_with(value)
+        .validate()?;
+    Ok((c, (key, value)))
+}
+
+fn next_member(s: &str) -> Result<(&str, Member<'_>), ParsingError> {
+    Cursor::from(s).text(",").parse_with(member).validate()
+}
+
+/// a quoted string, with escapes decoded. Borrowed from the input unless it contains escapes
+pub fn string(s: &str) -> Result<(&str, Cow<'_, str>), ParsingError> {
+    Cursor::from(s)
+        .debug_context("string")
+        .text("\"")
+        .select(string_body)
+        .parse_selection_with(unescape)
+        .text("\"")
+        .validate()
+}
+
+// unescaped characters exclude quote, backslash and control characters
+fn string_body<'a, C: Matchable<'a> + Clone>(c: C) -> C {
+    c.repeat(0.., |c| match c.str() {
+        Ok(s) if s.starts_with('\\') => escape(c),
+        _ => c.chars_match(1.., |ch| ch >= ' ' && ch != '"' && ch != '\\'),
+    })
+}
+
+fn escape<'a, C: Matchable<'a>>(c: C) -> C {
+    let c = c.text("\\");
+    match c.str() {
+        Ok(s) if s.starts_with('u') => c.text("u").chars_match(4..=4, |ch| ch.is_ascii_hexdigit()),
+        _ => c.chars_in(1..=1, ESCAPES),
+    }
+}
+
+// the body has already been checked by string_body, so escapes are well formed,
+// other than \u surrogates, which must pair up
+fn unescape(body: &str) -> Result<Cow<'_, str>, ParsingError> {
+    if !body.contains('\\') {
+        return Ok(Cow::Borrowed(body));
+    }
+    let mut s = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(i) = rest.find('\\') {
+        s.push_str(&rest[..i]);
+        let esc = &rest[i + 1..];
+        rest = &esc[1..];
+        let ch = match esc.as_bytes()[0] {
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let (ch, r) = unicode_escape(rest).ok_or_else(|| {
+                    ParsingError::conversion::<char>(&esc[..5], "unpaired surrogate")
+                })?;
+                rest = r;
+                ch
+            }
+            other => other as char,
+        };
+        s.push(ch);
+    }
+    s.push_str(rest);
+    Ok(Cow::Owned(s))
+}
+
+// the hex digits of a \u escape, and a second escape if the first is a high surrogate
+fn unicode_escape(s: &str) -> Option<(char, &str)> {
+    let hex = |s: &str| u32::from_str_radix(s.get(..4)?, 16).ok();
+    let cp = hex(s)?;
+    match cp {
+        0xD800..=0xDBFF => {
+            let low = hex(s[4..].strip_prefix("\\u")?)?;
+            if !(0xDC00..=0xDFFF).contains(&low) {
+                return None;
+            }
+            let cp = 0x10000 + ((cp - 0xD800) << 10) + (low - 0xDC00);
+            Some((char::from_u32(cp)?, &s[10..]))
+        }
+        _ => Some((char::from_u32(cp)?, &s[4..])),
+    }
+}
+
+/// a number, `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`
+pub fn number(s: &str) -> Result<(&str, Number<'_>), ParsingError> {
+    Cursor::from(s)
+        .debug_context("number")
+        .select(number_lexer)
+        .parse_selection_as_str()
+        .validate()
+        .map(|(c, text)| (c, Number(text)))
+}
+
+fn number_lexer<'a, C: Matchable<'a>>(c: C) -> C {
+    let c = c.maybe("-");
+    let c = match c.str() {
+        Ok(s) if s.starts_with('0') => c.text("0"),
+        _ => c.chars_in(1..=1, ONE_TO_NINE).digits(0..),
+    };
+    let c = match c.str() {
+        Ok(s) if s.starts_with('.') => c.text(".").digits(1..),
+        _ => c,
+    };
+    match c.str() {
+        Ok(s) if s.starts_with(['e', 'E']) => c
+            .chars_in(1..=1, &['e', 'E'])
+            .chars_in(0..=1, &['+', '-'])
+            .digits(1..),
+        _ => c,
+    }
+}
+
+/// A step of a streamed document, as produced by [`events`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    /// an object member's key, which is followed by its value
+    Key(Cow<'a, str>),
+    Null,
+    Bool(bool),
+    Number(Number<'a>),
+    String(Cow<'a, str>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Value,
+    ValueOrEnd,
+    Key,
+    KeyOrEnd,
+    CommaOrEnd,
+    EndOfInput,
+}
+
+/// Iterator over the [`Event`]s of a document, created by [`events`]
+#[derive(Debug, Clone)]
+pub struct Events<'a> {
+    rest: &'a str,
+    // b'[' or b'{' for each open array or object
+    stack: Vec<u8>,
+    expect: Expect,
+    done: bool,
+}
+
+/// Streams a JSON document as events. The document is checked as it is read,
+/// so the iterator ends with an error at the first problem.
+///
+/// ```
+/// use daisychain::contrib::json::{self, Event};
+///
+/// let keys: Vec<_> = json::events(r#"{"a": 1, "b": {"c": []}}"#)
+///     .filter_map(|e| match e {
+///         Ok(Event::Key(k)) => Some(k),
+///         _ => None,
+///     })
+///     .collect();
+/// assert_eq!(keys, ["a", "b", "c"]);
+/// assert!(json::events("[1,]").any(|e| e.is_err()));
+/// ```
+pub fn events(s: &str) -> Events<'_> {
+    Events {
+        rest: s,
+        stack: vec![],
+        expect: Expect::Value,
+        done: false,
+    }
+}
+
+impl<'a> Events<'a> {
+    fn step(&mut self, s: &'a str) -> Result<Option<(&'a str, Event<'a>)>, ParsingError> {
+        let close = |stack: &[u8]| match stack.last() {
+            Some(b'[') => (']', Event::EndArray),
+            _ => ('}', Event::EndObject),
+        };
+        match self.expect {
+            Expect::EndOfInput => {
+                Cursor::from(s).end_of_stream().validate()?;
+                Ok(None)
+            }
+            Expect::ValueOrEnd | Expect::KeyOrEnd | Expect::CommaOrEnd => {
+                let (ch, event) = close(&self.stack);
+                if let Some(rest) = s.strip_prefix(ch) {
+                    self.stack.pop();
+                    self.after_value();
+                    return Ok(Some((rest, event)));
+                }
+                match self.expect {
+                    Expect::ValueOrEnd => self.expect = Expect::Value,
+                    Expect::KeyOrEnd => self.expect = Expect::Key,
+                    _ => {
+                        let rest = Cursor::from(s).text(",").chars_in(0.., WS).validate()?;
+                        self.expect = match ch {
+                            ']' => Expect::Value,
+                            _ => Expect::Key,
+                        };
+                        return self.step(rest);
+                    }
+                }
+                self.step(s)
+            }
+            Expect::Key => {
+                let (c, key) = Cursor::from(s)
+                    .parse_with(string)
+                    .chars_in(0.., WS)
+                    .text(":")
+                    .validate()?;
+                self.expect = Expect::Value;
+                Ok(Some((c, Event::Key(key))))
+            }
+            Expect::Value => {
+                let (c, event) = match s.as_bytes().first() {
+                    Some(&b) if b == b'[' || b == b'{' => {
+                        self.stack.push(b);
+                        self.expect = match b {
+                            b'[' => Expect::ValueOrEnd,
+                            _ => Expect::KeyOrEnd,
+                        };
+                        let event = match b {
+                            b'[' => Event::StartArray,
+                            _ => Event::StartObject,
+                        };
+                        return Ok(Some((&s[1..], event)));
+                    }
+                    _ => scalar(s)?,
+                };
+                self.after_value();
+                let event = match event {
+                    Value::Null => Event::Null,
+                    Value::Bool(b) => Event::Bool(b),
+                    Value::Number(n) => Event::Number(n),
+                    Value::String(s) => Event::String(s),
+                    Value::Array(_) | Value::Object(_) => unreachable!("scalar"),
+                };
+                Ok(Some((c, event)))
+            }
+        }
+    }
+
+    fn after_value(&mut self) {
+        self.expect = match self.stack.is_empty() {
+            true => Expect::EndOfInput,
+            false => Expect::CommaOrEnd,
+        };
+    }
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = Result<Event<'a>, ParsingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let s = self.rest.trim_start_matches(WS);
+        match self.step(s) {
+            Ok(Some((rest, event))) => {
+                self.rest = rest;
+                Some(Ok(event))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+    use test_log::test;
+
+    #[test]
+    fn test_values() {
+        let v =
+            parse(r#" {"a": [1, -0.5e-3, "x\"y", true, false, null], "b": {}, "c": []} "#).unwrap();
+        assert_eq!(v["a"][0].as_i64(), Some(1));
+        assert_eq!(v["a"][1].as_f64(), Some(-0.0005));
+        assert_eq!(v["a"][2].as_str(), Some("x\"y"));
+        assert_eq!(v["a"][3].as_bool(), Some(true));
+        assert_eq!(v["a"][5].is_null(), true);
+        assert_eq!(v["b"], Value::Object(vec![]));
+        assert_eq!(v["c"], Value::Array(vec![]));
+        assert_eq!(v["missing"][7].is_null(), true);
+
+        // strings are borrowed unless escaped
+        assert!(matches!(&v["a"][2], Value::String(Cow::Owned(_))));
+        let v = parse(r#"["plain"]"#).unwrap();
+        assert!(matches!(&v[0], Value::String(Cow::Borrowed("plain"))));
+
+        let v = parse(r#"{"k": 1, "k": 2}"#).unwrap();
+        assert_eq!(v["k"].as_i64(), Some(2));
+        assert_eq!(v.as_object().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_strings() {
+        let s = |s| string(s).map(|(_, s)| s.into_owned());
+        assert_eq!(s(r#""é\n\/\\""#).unwrap(), "é\n/\\");
+        assert_eq!(s(r#""😀""#).unwrap(), "😀");
+        assert_eq!(s(r#""\u00e9abc\"""#).unwrap(), "éabc\"");
+        assert_eq!(s(r#""\uD800""#).is_err(), true);
+        assert_eq!(s(r#""\uDC00\uD800""#).is_err(), true);
+        assert_eq!(s("\"tab\there\"").is_err(), true);
+        assert_eq!(s(r#""\x""#).is_err(), true);
+
+        let v = Value::String("q\"\u{1}\n".into());
+        assert_eq!(v.to_string(), r#""q\"\u0001\n""#);
+        assert_eq!(parse(&v.to_string()).unwrap(), v);
+    }
+
+    #[test]
+    fn test_numbers() {
+        for ok in ["0", "-0", "12", "1.5", "-1.5e10", "1E+2", "0e-1"] {
+            assert_eq!(parse(ok).unwrap(), Value::Number(Number(ok)), "{ok}");
+        }
+        for bad in [
+            "01", "-", "1.", ".5", "1e", "+1", "0x1", "1.5e+", "Infinity",
+        ] {
+            assert_eq!(parse(bad).is_err(), true, "{bad}");
+        }
+        assert_eq!(number("12,").unwrap(), (",", Number("12")));
+        let big = Number("123456789012345678901234567890");
+        assert_eq!((big.as_i64(), big.as_f64()), (None, 1.2345678901234568e29));
+    }
+
+    #[test]
+    fn test_events() {
+        let events: Vec<_> = events(r#" {"a": [1, "x"], "b": null} "#)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            events,
+            [
+                Event::StartObject,
+                Event::Key("a".into()),
+                Event::StartArray,
+                Event::Number(Number("1")),
+                Event::String("x".into()),
+                Event::EndArray,
+                Event::Key("b".into()),
+                Event::Null,
+                Event::EndObject,
+            ]
+        );
+        let last = super::events("[1 2]").last().unwrap();
+        assert_eq!(last.is_err(), true);
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let nested = "[".repeat(150) + &"]".repeat(150);
+        assert_eq!(parse(&nested).is_ok(), true);
+        let objects = r#"{"a":"#.repeat(90) + "1" + &"}".repeat(90);
+        assert_eq!(parse(&objects).is_ok(), true);
+        let too_deep = "[".repeat(300) + &"]".repeat(300);
+        assert_eq!(parse(&too_deep).is_err(), true);
+        // streaming keeps its own stack, so has no limit
+        assert_eq!(super::events(&too_deep).all(|e| e.is_ok()), true);
+
+        let hostile = "[".repeat(100_000);
+        assert_eq!(parse(&hostile).is_err(), true);
+        assert_eq!(super::events(&hostile).last().unwrap().is_err(), true);
+
+        // the caller's limits still apply within parse
+        let long = format!("[{}0]", "0,".repeat(5000));
+        let in_budget = |s| parse(s).map(|v| ("", v));
+        assert_eq!(
+            Limits::new().max_steps(50).parse(&long, in_budget).is_err(),
+            true
+        );
+        assert_eq!(
+            Limits::new()
+                .max_depth(2)
+                .parse("[[[]]]", in_budget)
+                .is_err(),
+            true
+        );
+        assert_eq!(
+            Limits::new()
+                .max_depth(3)
+                .parse("[[[]]]", in_budget)
+                .is_ok(),
+            true
+        );
+    }
+
+    // cases from JSONTestSuite (test_parsing, see the fixtures README): y_ must
+    // be accepted, n_ rejected, and i_ may be either, but must not panic
+    #[test]
+    fn test_json_test_suite() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/json");
+        let mut failures = vec![];
+        let mut count = 0;
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            if !name.ends_with(".json") {
+                continue;
+            }
+            let bytes = fs::read(&path).unwrap();
+            let (parsed, streamed) = match std::str::from_utf8(&bytes) {
+                Ok(s) => (parse(s).is_ok(), events(s).all(|e| e.is_ok())),
+                Err(_) => (false, false),
+            };
+            let expected = match &name[..2] {
+                "y_" => Some(true),
+                "n_" => Some(false),
+                _ => None,
+            };
+            if let Some(expected) = expected {
+                if parsed != expected || streamed != expected {
+                    failures.push(format!("{name}: parsed {parsed} streamed {streamed}"));
+                }
+            }
+            count += 1;
+        }
+        assert_eq!(count, 95 + 188 + 35);
+        assert!(failures.is_empty(), "{failures:#?}");
+    }
+}
@@ -0,0 +1,664 @@
+//! Log lines: Apache/Nginx combined (and common) access logs, RFC 3164 (BSD) and
+//! RFC 5424 syslog, and logfmt `key=value` lines.
+//!
+//! Each parser takes a single line and returns a struct borrowing from it. [`lines`]
+//! applies one to every non-blank line of a file, either stopping at the first bad line,
+//! or with [`Lines::skip_errors`] skipping bad lines and keeping a [`LogError`] for each.
+//!
+//! ```
+//! use daisychain::contrib::logs;
+//!
+//! let text = r#"127.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] "GET /a.gif HTTP/1.0" 200 2326 "http://example.com/" "Mozilla/4.08"
+//! not a log line
+//! 10.0.0.2 - - [10/Oct/2000:13:56:01 -0700] "POST /login HTTP/1.1" 302 - "-" "curl/8.0"
+//! "#;
+//! let mut lines = logs::lines(text, logs::combined);
+//! let entries: Vec<_> = lines.skip_errors().collect();
+//! assert_eq!(entries.len(), 2);
+//! assert_eq!(entries[0].user, Some("frank"));
+//! assert_eq!(entries[0].request_parts(), Some(("GET", "/a.gif", "HTTP/1.0")));
+//! assert_eq!((entries[1].status, entries[1].bytes, entries[1].referer), (302, None, None));
+//! assert_eq!(lines.errors()[0].line, 2);
+//!
+//! let line = logs::logfmt(r#"level=info msg="user logged in" user_id=42 cached"#).unwrap();
+//! assert_eq!(line.get("msg"), Some("user logged in"));
+//! assert_eq!(line.get("cached"), Some(""));
+//! ```
+//!
+//! Fields written as `-` are `None`. Quoted access log fields and RFC 3164 timestamps are
+//! kept as written, while RFC 5424 parameter values and logfmt values are unescaped.
+use std::borrow::Cow;
+
+use super::datetime::{self, DateTime};
+use super::unescape;
+use crate::prelude::*;
+
+/// An access log line in the Apache/Nginx combined format, or the common format without
+/// the referer and user agent:
+/// `host ident user [10/Oct/2000:13:55:36 -0700] "request" status bytes "referer" "agent"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Access<'a> {
+    pub client: &'a str,
+    pub ident: Option<&'a str>,
+    pub user: Option<&'a str>,
+    pub time: DateTime,
+    /// the request line, eg `GET /index.html HTTP/1.1`, with any `\"` escapes
+    pub request: &'a str,
+    pub status: u16,
+    pub bytes: Option<u64>,
+    pub referer: Option<&'a str>,
+    pub user_agent: Option<&'a str>,
+}
+
+impl<'a> Access<'a> {
+    /// the method, target and protocol of the request line, if it has all three
+    pub fn request_parts(&self) -> Option<(&'a str, &'a str, &'a str)> {
+        let mut parts = self.request.split(' ');
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(target), Some(protocol), None) => Some((method, target, protocol)),
+            _ => None,
+        }
+    }
+}
+
+/// The `<PRI>` of a syslog message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Priority {
+    /// 0 (kernel) to 23 (local7)
+    pub facility: u8,
+    /// 0 (emergency) to 7 (debug)
+    pub severity: u8,
+}
+
+/// A BSD syslog line: `<34>Oct 11 22:14:15 mymachine su[123]: 'su root' failed`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rfc3164<'a> {
+    pub priority: Priority,
+    /// `Mmm dd hh:mm:ss`, without a year, and with a single digit day padded by a space
+    pub timestamp: &'a str,
+    pub hostname: &'a str,
+    /// the program name, when followed by an optional `[pid]` and a `:`
+    pub tag: Option<&'a str>,
+    pub pid: Option<&'a str>,
+    pub message: &'a str,
+}
+
+/// An RFC 5424 syslog line:
+/// `<165>1 2003-10-11T22:14:15.003Z host app 1234 ID47 [id key="value"] message`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rfc5424<'a> {
+    pub priority: Priority,
+    pub version: u8,
+    pub timestamp: Option<DateTime>,
+    pub hostname: Option<&'a str>,
+    pub app_name: Option<&'a str>,
+    pub proc_id: Option<&'a str>,
+    pub msg_id: Option<&'a str>,
+    pub structured_data: Vec<SdElement<'a>>,
+    /// without any leading byte order mark
+    pub message: Option<&'a str>,
+}
+
+/// A structured data element: `[exampleSDID@32473 iut="3" eventSource="Application"]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdElement<'a> {
+    pub id: &'a str,
+    pub params: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+/// A logfmt line: `level=info msg="hello world" took=12ms`. A bare key has an empty value
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Logfmt<'a> {
+    /// in line order, including any duplicate keys
+    pub pairs: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+impl<'a> Logfmt<'a> {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .rev()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_ref())
+    }
+}
+
+/// A line of a log which could not be parsed, numbered from 1
+pub type LogError = super::LineError;
+
+/// The non-blank lines of `text`, parsed by `parser`, eg [`combined`]
+pub fn lines<'a, T>(text: &'a str, parser: fn(&'a str) -> Result<T, ParsingError>) -> Lines<'a, T> {
+    Lines {
+        rest: text,
+        line: 0,
+        parser,
+        errors: vec![],
+    }
+}
+
+/// An iterator of parsed lines, created by [`lines`]
+pub struct Lines<'a, T> {
+    rest: &'a str,
+    line: usize,
+    parser: fn(&'a str) -> Result<T, ParsingError>,
+    errors: Vec<LogError>,
+}
+
+impl<'a, T> Lines<'a, T> {
+    /// the lines which parse, recording those which do not in [`errors`](Self::errors)
+    pub fn skip_errors(&mut self) -> SkipErrors<'_, 'a, T> {
+        SkipErrors(self)
+    }
+
+    /// the lines skipped so far by [`skip_errors`](Self::skip_errors)
+    pub fn errors(&self) -> &[LogError] {
+        &self.errors
+    }
+}
+
+impl<'a, T> Iterator for Lines<'a, T> {
+    type Item = Result<T, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.rest.is_empty() {
+            let (line, rest) = self.rest.split_once('\n').unwrap_or((self.rest, ""));
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            self.rest = rest;
+            self.line += 1;
+            if !line.trim().is_empty() {
+                return Some((self.parser)(line).map_err(|source| LogError {
+                    line: self.line,
+                    text: line.to_string(),
+                    source,
+                }));
+            }
+        }
+        None
+    }
+}
+
+/// An iterator of the lines which parse, created by [`Lines::skip_errors`]
+pub struct SkipErrors<'l, 'a, T>(&'l mut Lines<'a, T>);
+
+impl<'l, 'a, T> Iterator for SkipErrors<'l, 'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            match self.0.next()? {
+                Ok(t) => return Some(t),
+                Err(e) => self.0.errors.push(e),
+            }
+        }
+    }
+}
+
+/// An Apache/Nginx combined or common format access log line
+pub fn combined(line: &str) -> Result<Access<'_>, ParsingError> {
+    let (c, client, ident, user) = Cursor::from(line)
+        .debug_context("combined")
+        .select(field)
+        .parse_selection_as_str()
+        .text(" ")
+        .select(field)
+        .parse_selection_as_str()
+        .text(" ")
+        .select(field)
+        .parse_selection_as_str()
+        .text(" [")
+        .validate()?;
+    let (c, time, request) = Cursor::from(c)
+        .parse_with(datetime::clf)
+        .text("] \"")
+        .select(quoted_body)
+        .parse_selection_as_str()
+        .text("\" ")
+        .validate()?;
+    let (c, status, bytes) = Cursor::from(c)
+        .digits(3..=3)
+        .parse_selection::<u16>()
+        .text(" ")
+        .select(field)
+        .parse_selection_as_str()
+        .try_map_last(|bytes| nil(bytes).map(str::parse::<u64>).transpose())
+        .validate()?;
+    let (c, referer, user_agent) = match Cursor::from(c)
+        .text(" \"")
+        .select(quoted_body)
+        .parse_selection_as_str()
+        .text("\" \"")
+        .select(quoted_body)
+        .parse_selection_as_str()
+        .text("\"")
+        .validate()
+    {
+        Ok((c, referer, agent)) => (c, nil(referer), nil(agent)),
+        Err(_) => (c, None, None),
+    };
+    Cursor::from(c).hws().end_of_stream().validate()?;
+    Ok(Access {
+        client,
+        ident: nil(ident),
+        user: nil(user),
+        time,
+        request,
+        status,
+        bytes,
+        referer,
+        user_agent,
+    })
+}
+
+/// A BSD syslog line, following RFC 3164
+pub fn rfc3164(line: &str) -> Result<Rfc3164<'_>, ParsingError> {
+    let (c, priority, timestamp, hostname) = Cursor::from(line)
+        .debug_context("rfc3164")
+        .parse_with(priority)
+        .select(bsd_timestamp)
+        .parse_selection_as_str()
+        .try_map_last(|t| datetime::month_number(&t[..3]).map(|_| t))
+        .text(" ")
+        .select(field)
+        .parse_selection_as_str()
+        .text(" ")
+        .validate()?;
+    let (message, tag, pid) = match tag(c) {
+        Ok((message, (tag, pid))) => (message, Some(tag), pid),
+        Err(_) => (c, None, None),
+    };
+    Ok(Rfc3164 {
+        priority,
+        timestamp,
+        hostname,
+        tag,
+        pid,
+        message,
+    })
+}
+
+/// An RFC 5424 syslog line
+pub fn rfc5424(line: &str) -> Result<Rfc5424<'_>, ParsingError> {
+    let (c, priority, version) = Cursor::from(line)
+        .debug_context("rfc5424")
+        .parse_with(priority)
+        .digits(1..=2)
+        .parse_selection::<u8>()
+        .text(" ")
+        .validate()?;
+    let (c, timestamp) = match c.strip_prefix('-') {
+        Some(c) => (c, None),
+        None => datetime::rfc3339(c).map(|(c, t)| (c, Some(t)))?,
+    };
+    let (c, hostname, app_name) = Cursor::from(c)
+        .text(" ")
+        .select(field)
+        .parse_selection_as_str()
+        .text(" ")
+        .select(field)
+        .parse_selection_as_str()
+        .validate()?;
+    let (c, proc_id, msg_id) = Cursor::from(c)
+        .text(" ")
+        .select(field)
+        .parse_selection_as_str()
+        .text(" ")
+        .select(field)
+        .parse_selection_as_str()
+        .text(" ")
+        .validate()?;
+    let (c, structured_data) = match c.strip_prefix('-') {
+        Some(c) => (c, vec![]),
+        None => Cursor::from(c)
+            .parse_struct_vec(sd_element)
+            .validate()
+            .and_then(|(c, sd)| match sd.is_empty() {
+                true => Err(ParsingError::NoMatch {
+                    action: "structured data",
+                    args: "",
+                }),
+                false => Ok((c, sd)),
+            })?,
+    };
+    let message = match c.strip_prefix(' ') {
+        Some(msg) => Some(msg.strip_prefix('\u{feff}').unwrap_or(msg)),
+        None => Cursor::from(c).end_of_stream().validate().map(|_| None)?,
+    };
+    Ok(Rfc5424 {
+        priority,
+        version,
+        timestamp,
+        hostname: nil(hostname),
+        app_name: nil(app_name),
+        proc_id: nil(proc_id),
+        msg_id: nil(msg_id),
+        structured_data,
+        message,
+    })
+}
+
+/// A logfmt line of space separated `key=value`, `key="quoted value"` or bare `key` pairs
+pub fn logfmt(line: &str) -> Result<Logfmt<'_>, ParsingError> {
+    let (_, pairs) = Cursor::from(line)
+        .debug_context("logfmt")
+        .hws()
+        .parse_struct_vec(logfmt_pair)
+        .end_of_stream()
+        .validate()?;
+    Ok(Logfmt { pairs })
+}
+
+fn logfmt_pair(s: &str) -> Result<(&str, (&str, Cow<'_, str>)), ParsingError> {
+    let (c, key) = Cursor::from(s)
+        .chars_match(1.., |ch| !ch.is_whitespace() && ch != '=' && ch != '"')
+        .parse_selection_as_str()
+        .validate()?;
+    let (c, value) = match c.strip_prefix('=') {
+        Some(c) if c.starts_with('"') => Cursor::from(c)
+            .text("\"")
+            .select(quoted_body)
+            .parse_selection_with(unescape)
+            .text("\"")
+            .validate()?,
+        Some(c) => Cursor::from(c)
+            .chars_match(0.., |ch| !ch.is_whitespace() && ch != '"')
+            .parse_selection_as_str()
+            .validate()
+            .map(|(c, v)| (c, Cow::Borrowed(v)))?,
+        None => (c, Cow::Borrowed("")),
+    };
+    let c = match c.is_empty() {
+        true => c,
+        false => Cursor::from(c)
+            .chars_match(1.., char::is_whitespace)
+            .validate()?,
+    };
+    Ok((c, (key, value)))
+}
+
+// `<PRI>`, with a facility of at most 23
+fn priority(s: &str) -> Result<(&str, Priority), ParsingError> {
+    let (c, pri) = Cursor::from(s)
+        .text("<")
+        .digits(1..=3)
+        .parse_selection::<u8>()
+        .verify_last(|pri| *pri <= 191)
+        .text(">")
+        .validate()?;
+    Ok((
+        c,
+        Priority {
+            facility: pri / 8,
+            severity: pri % 8,
+        },
+    ))
+}
+
+// `Oct 11 22:14:15` or `Oct  1 22:14:15`
+fn bsd_timestamp<'a, C: Matchable<'a>>(c: C) -> C {
+    c.chars_match(3..=3, |ch| ch.is_ascii_alphabetic())
+        .text(" ")
+        .chars_in(0..=1, &[' '])
+        .digits(1..=2)
+        .text(" ")
+        .digits(2..=2)
+        .text(":")
+        .digits(2..=2)
+        .text(":")
+        .digits(2..=2)
+}
+
+// `tag[pid]: ` or `tag: `, returning the message after it
+fn tag(s: &str) -> Result<(&str, (&str, Option<&str>)), ParsingError> {
+    let (c, tag) = Cursor::from(s)
+        .chars_match(1.., |ch| !ch.is_whitespace() && ch != ':' && ch != '[')
+        .parse_selection_as_str()
+        .validate()?;
+    let (c, pid) = match c.strip_prefix('[') {
+        Some(c) => Cursor::from(c)
+            .chars_not_in(1.., &[']'])
+            .parse_selection_as_str()
+            .text("]")
+            .validate()
+            .map(|(c, pid)| (c, Some(pid)))?,
+        None => (c, None),
+    };
+    let c = Cursor::from(c).text(":").hws().validate()?;
+    Ok((c, (tag, pid)))
+}
+
+// `[id name="value" ...]`
+fn sd_element(s: &str) -> Result<(&str, SdElement<'_>), ParsingError> {
+    let (c, id, params) = Cursor::from(s)
+        .debug_context("sd_element")
+        .text("[")
+        .chars_match(1.., is_sd_name)
+        .parse_selection_as_str()
+        .parse_struct_vec(sd_param)
+        .text("]")
+        .validate()?;
+    Ok((c, SdElement { id, params }))
+}
+
+fn sd_param(s: &str) -> Result<(&str, (&str, Cow<'_, str>)), ParsingError> {
+    let (c, name, value) = Cursor::from(s)
+        .text(" ")
+        .chars_match(1.., is_sd_name)
+        .parse_selection_as_str()
+        .text("=\"")
+        .select(quoted_body)
+        .parse_selection_with(unescape_sd)
+        .text("\"")
+        .validate()?;
+    Ok((c, (name, value)))
+}
+
+fn is_sd_name(ch: char) -> bool {
+    ch.is_ascii_graphic() && !matches!(ch, '=' | ']' | '"')
+}
+
+// a field, or `-` for none
+fn field<'a, C: Matchable<'a>>(c: C) -> C {
+    c.chars_match(1.., |ch| !ch.is_whitespace())
+}
+
+fn nil(s: &str) -> Option<&str> {
+    (s != "-").then_some(s)
+}
+
+// up to an unescaped `"`
+fn quoted_body<'a, C: Matchable<'a> + Clone>(c: C) -> C {
+    c.repeat(0.., |c| match c.str() {
+        Ok(s) if s.starts_with('\\') => c.text("\\").chars_any(1..=1),
+        _ => c.chars_not_in(1.., &['"', '\\']),
+    })
+}
+
+// RFC 5424 parameter values, where only `\"`, `\\` and `\]` are escapes
+fn unescape_sd(body: &str) -> Result<Cow<'_, str>, ParsingError> {
+    if !body.contains('\\') {
+        return Ok(Cow::Borrowed(body));
+    }
+    let mut s = String::with_capacity(body.len());
+    let mut chars = body.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('\\', Some(&next @ ('"' | '\\' | ']'))) => {
+                s.push(next);
+                chars.next();
+            }
+            (ch, _) => s.push(ch),
+        }
+    }
+    Ok(Cow::Owned(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+    use test_log::test;
+
+    #[test]
+    fn test_combined() {
+        let line = r#"192.0.2.9 - - [01/Feb/2024:08:00:01 +0000] "GET /search?q=\"x\" HTTP/2.0" 404 0 "-" "Mozilla/5.0 (X11; Linux)""#;
+        let access = combined(line).unwrap();
+        assert_eq!(access.client, "192.0.2.9");
+        assert_eq!((access.ident, access.user), (None, None));
+        assert_eq!(access.time.to_string(), "2024-02-01T08:00:01Z");
+        assert_eq!(access.request, r#"GET /search?q=\"x\" HTTP/2.0"#);
+        assert_eq!((access.status, access.bytes), (404, Some(0)));
+        assert_eq!(access.referer, None);
+        assert_eq!(access.user_agent, Some("Mozilla/5.0 (X11; Linux)"));
+
+        // the common format, and a request line which is not a request
+        let access = combined(r#"h - u [01/Feb/2024:08:00:01 +0000] "\x16\x03" 400 150"#).unwrap();
+        assert_eq!((access.user, access.user_agent), (Some("u"), None));
+        assert_eq!(access.request_parts(), None);
+
+        for bad in [
+            "",
+            r#"h - - [01/Feb/2024:08:00:01 +0000] "GET / HTTP/1.1" 200"#,
+            r#"h - - [01/Feb/2024:08:00:01] "GET / HTTP/1.1" 200 1"#,
+            r#"h - - [01/Feb/2024:08:00:01 +0000] "GET / HTTP/1.1" 2000 1"#,
+            r#"h - - [01/Feb/2024:08:00:01 +0000] "GET / HTTP/1.1" 200 x"#,
+            r#"h - - [01/Feb/2024:08:00:01 +0000] "GET / HTTP/1.1 200 1"#,
+            r#"h - - [01/Feb/2024:08:00:01 +0000] "GET / HTTP/1.1" 200 1 "-" "ua" extra"#,
+        ] {
+            assert_eq!(combined(bad).is_err(), true, "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_rfc3164() {
+        let entry =
+            rfc3164("<34>Oct 11 22:14:15 mymachine su[230]: 'su root' failed for lonvick").unwrap();
+        assert_eq!(
+            entry.priority,
+            Priority {
+                facility: 4,
+                severity: 2
+            }
+        );
+        assert_eq!(entry.timestamp, "Oct 11 22:14:15");
+        assert_eq!(entry.hostname, "mymachine");
+        assert_eq!((entry.tag, entry.pid), (Some("su"), Some("230")));
+        assert_eq!(entry.message, "'su root' failed for lonvick");
+
+        let entry = rfc3164("<13>Feb  5 17:32:18 10.0.0.99 postfix/smtpd: connect from x").unwrap();
+        assert_eq!(entry.timestamp, "Feb  5 17:32:18");
+        assert_eq!((entry.tag, entry.pid), (Some("postfix/smtpd"), None));
+        assert_eq!(entry.message, "connect from x");
+
+        let entry = rfc3164("<0>Jan  1 00:00:00 host just a message").unwrap();
+        assert_eq!((entry.tag, entry.message), (None, "just a message"));
+
+        for bad in [
+            "Oct 11 22:14:15 host su: x",
+            "<192>Oct 11 22:14:15 host su: x",
+            "<34>Foo 11 22:14:15 host su: x",
+            "<34>Oct 11 22:14 host su: x",
+            "<34>Oct 11 22:14:15",
+        ] {
+            assert_eq!(rfc3164(bad).is_err(), true, "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_rfc5424() {
+        let line = r#"<165>1 2003-10-11T22:14:15.003Z mymachine.example.com evntslog - ID47 [exampleSDID@32473 iut="3" eventSource="Application" eventID="1011"][examplePriority@32473 class="high \"a\\b\] c"] An application event log entry..."#;
+        let entry = rfc5424(line).unwrap();
+        assert_eq!(
+            entry.priority,
+            Priority {
+                facility: 20,
+                severity: 5
+            }
+        );
+        assert_eq!(entry.version, 1);
+        assert_eq!(
+            entry.timestamp.unwrap().to_string(),
+            "2003-10-11T22:14:15.003Z"
+        );
+        assert_eq!(entry.hostname, Some("mymachine.example.com"));
+        assert_eq!(
+            (entry.app_name, entry.proc_id, entry.msg_id),
+            (Some("evntslog"), None, Some("ID47"))
+        );
+        assert_eq!(entry.structured_data.len(), 2);
+        assert_eq!(entry.structured_data[0].id, "exampleSDID@32473");
+        assert_eq!(
+            entry.structured_data[0].params[1],
+            ("eventSource", Cow::Borrowed("Application"))
+        );
+        assert_eq!(entry.structured_data[1].params[0].1, "high \"a\\b] c");
+        assert_eq!(entry.message, Some("An application event log entry..."));
+
+        let entry = rfc5424("<34>1 - - - - - -").unwrap();
+        assert_eq!(
+            (entry.timestamp, entry.hostname, entry.message),
+            (None, None, None)
+        );
+        assert_eq!(entry.structured_data, vec![]);
+
+        let entry = rfc5424(
+            "<34>1 2003-08-24T05:14:15.000003-07:00 host su - ID47 - \u{feff}BOM'su root' failed",
+        )
+        .unwrap();
+        assert_eq!(entry.message, Some("BOM'su root' failed"));
+        assert_eq!(entry.timestamp.unwrap().offset.unwrap().seconds, -7 * 3600);
+
+        for bad in [
+            "<34> 2003-10-11T22:14:15Z host app - - -",
+            "<34>1 2003-10-11T22:14:15 host app - - -",
+            "<34>1 - host app - - [unterminated",
+            "<34>1 - host app - - [id key=unquoted]",
+            "<34>1 - host app - -x",
+            "<34>1 - host app -",
+        ] {
+            assert_eq!(rfc5424(bad).is_err(), true, "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_logfmt() {
+        let line =
+            logfmt(r#"  at=info method=GET path="/a b" status=200 msg="say \"hi\"\n" empty= flag"#)
+                .unwrap();
+        let keys: Vec<_> = line.pairs.iter().map(|(k, _)| *k).collect();
+        assert_eq!(
+            keys,
+            ["at", "method", "path", "status", "msg", "empty", "flag"]
+        );
+        assert_eq!(line.get("path"), Some("/a b"));
+        assert_eq!(line.get("msg"), Some("say \"hi\"\n"));
+        assert_eq!((line.get("empty"), line.get("flag")), (Some(""), Some("")));
+        assert_eq!(line.get("missing"), None);
+        assert_eq!(logfmt("a=1 a=2").unwrap().get("a"), Some("2"));
+        assert_eq!(logfmt("").unwrap(), Logfmt::default());
+
+        for bad in [r#"msg="unterminated"#, r#"a="x"b"#, "=value", r#"a=b"c""#] {
+            assert_eq!(logfmt(bad).is_err(), true, "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_lines() {
+        let text = "a=1\r\n\n  \nbad=\"\nb=2\nc=\"3\"x\n";
+        let all: Vec<_> = lines(text, logfmt).collect();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].as_ref().unwrap().get("a"), Some("1"));
+        let err = all[1].as_ref().unwrap_err();
+        assert_eq!((err.line, err.text.as_str()), (4, "bad=\""));
+        assert_eq!(err.to_string(), "line 4: cannot parse 'bad=\"'");
+        assert_eq!(err.source().is_some(), true);
+
+        let mut lines = lines(text, logfmt);
+        let ok: Vec<_> = lines.skip_errors().map(|l| l.pairs[0].0).collect();
+        assert_eq!(ok, ["a", "b"]);
+        let skipped: Vec<_> = lines.errors().iter().map(|e| e.line).collect();
+        assert_eq!(skipped, [4, 6]);
+
+        let first_error = super::lines(text, logfmt).collect::<Result<Vec<_>, _>>();
+        assert_eq!(first_error.unwrap_err().line, 4);
+    }
+}
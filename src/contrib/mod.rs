@@ -1 +1,62 @@
-pub mod contrib_parsers;
\ No newline at end of file
+//! Ready-made parsers for common formats, built with daisychain
+
+use std::{borrow::Cow, error::Error, fmt};
+
+use crate::prelude::ParsingError;
+
+pub mod csv;
+pub mod datetime;
+pub mod email;
+pub mod ini;
+pub mod json;
+pub mod logs;
+pub mod net;
+pub mod uri;
+
+/// A line which could not be parsed, numbered from 1
+#[derive(Debug)]
+pub struct LineError {
+    pub line: usize,
+    pub text: String,
+    pub source: ParsingError,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: cannot parse '{}'", self.line, self.text)
+    }
+}
+
+impl Error for LineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<LineError> for ParsingError {
+    fn from(e: LineError) -> Self {
+        ParsingError::Fatal(Some(Box::new(e)))
+    }
+}
+
+// the body of a quoted string, with `\n`, `\t` and `\r`, and any other char escaped as itself
+pub(crate) fn unescape(body: &str) -> Result<Cow<'_, str>, ParsingError> {
+    if !body.contains('\\') {
+        return Ok(Cow::Borrowed(body));
+    }
+    let mut s = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(ch) = chars.next() {
+        s.push(match ch {
+            '\\' => match chars.next() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('r') => '\r',
+                Some(ch) => ch,
+                None => '\\',
+            },
+            ch => ch,
+        });
+    }
+    Ok(Cow::Owned(s))
+}
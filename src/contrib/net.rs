@@ -0,0 +1,612 @@
+//! Lexers for network addresses: IPv4, IPv6, CIDR blocks, MAC addresses and `host:port`.
+//!
+//! Each lexer matches the exact extent of an address, so that in a
+//! [`select`](crate::prelude::Matchable::select) the selection can be converted with
+//! [`parse_selection`](crate::prelude::Matchable::parse_selection) into the `std::net`
+//! types, or into [`Cidr`] and [`MacAddr`]. An address must end at a word boundary, so
+//! `1.2.3.4` is not found within `1.2.3.456` or `1.2.3.4.5`.
+//!
+//! ```
+//! use std::net::{IpAddr, SocketAddr};
+//! use daisychain::contrib::net::{self, Cidr};
+//! use daisychain::prelude::*;
+//!
+//! let line = "accept 192.0.2.7:51234 -> [2001:db8::1]:443 via 192.0.2.0/24";
+//! let (_, from, to, via) = Cursor::from(line)
+//!     .text("accept ")
+//!     .select(net::socket_addr)
+//!     .parse_selection::<SocketAddr>()
+//!     .text(" -> ")
+//!     .select(net::socket_addr)
+//!     .parse_selection::<SocketAddr>()
+//!     .text(" via ")
+//!     .select(net::cidr)
+//!     .parse_selection::<Cidr>()
+//!     .validate()
+//!     .unwrap();
+//! assert_eq!(from, "192.0.2.7:51234".parse().unwrap());
+//! assert_eq!(to.port(), 443);
+//! assert!(via.contains(from.ip()));
+//!
+//! let (_, host, port) = Cursor::from("db.internal:5432")
+//!     .select(net::hostname)
+//!     .parse_selection_as_str()
+//!     .text(":")
+//!     .select(|c| c.digits(1..=5))
+//!     .parse_selection::<u16>()
+//!     .validate()
+//!     .unwrap();
+//! assert_eq!((host, port), ("db.internal", 5432));
+//! ```
+//!
+//! IPv6 zone IDs such as `fe80::1%eth0` are part of the match, but `std::net` only
+//! accepts numeric zones, and only within a `SocketAddrV6` like `[fe80::1%2]:80`.
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use crate::prelude::*;
+use crate::text_parser::apply;
+
+/// A dotted-quad IPv4 address such as `192.0.2.1`, without leading zeros
+pub fn ipv4<'a, C: Matchable<'a>>(c: C) -> C {
+    lex(c, ipv4_token, "ipv4")
+}
+
+/// An IPv6 address with optional `::` compression, trailing IPv4 part and `%zone`
+pub fn ipv6<'a, C: Matchable<'a>>(c: C) -> C {
+    lex(c, ipv6_token, "ipv6")
+}
+
+/// An IPv4 or IPv6 address
+pub fn ip<'a, C: Matchable<'a>>(c: C) -> C {
+    lex(c, |s| ipv4_token(s).or_else(|| ipv6_token(s)), "ip")
+}
+
+/// An IPv4 or IPv6 address and prefix length, such as `10.0.0.0/8` or `2001:db8::/32`
+pub fn cidr<'a, C: Matchable<'a>>(c: C) -> C {
+    lex(c, cidr_token, "cidr")
+}
+
+/// Six hex bytes separated by `:` or `-`, or three groups of four hex digits separated by `.`
+pub fn mac<'a, C: Matchable<'a>>(c: C) -> C {
+    lex(c, mac_token, "mac")
+}
+
+/// A DNS host name of letters, digits and hyphens, whose last label is not all digits
+pub fn hostname<'a, C: Matchable<'a>>(c: C) -> C {
+    lex(c, hostname_token, "hostname")
+}
+
+/// An IP address and port, with IPv6 addresses in brackets: `1.2.3.4:80` or `[::1]:80`
+pub fn socket_addr<'a, C: Matchable<'a>>(c: C) -> C {
+    lex(
+        c,
+        |s| with_port(s, bracketed(s).or_else(|| ipv4_token(s))?),
+        "socket_addr",
+    )
+}
+
+/// A host name or IP address and port, such as `example.com:443`
+pub fn host_port<'a, C: Matchable<'a>>(c: C) -> C {
+    lex(
+        c,
+        |s| {
+            let host = bracketed(s)
+                .or_else(|| ipv4_token(s))
+                .or_else(|| hostname_token(s))?;
+            with_port(s, host)
+        },
+        "host_port",
+    )
+}
+
+/// An IP network such as `192.168.0.0/16`, as matched by [`cidr`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cidr {
+    pub addr: IpAddr,
+    /// the number of network bits. One over the address length (as only a hand-built
+    /// `Cidr` can have) is treated as the whole address
+    pub prefix: u8,
+}
+
+impl Cidr {
+    /// the first address of the block, with the host bits of `addr` cleared
+    pub fn network(&self) -> IpAddr {
+        match self.addr {
+            IpAddr::V4(a) => IpAddr::V4(Ipv4Addr::from(u32::from(a) & self.mask4())),
+            IpAddr::V6(a) => IpAddr::V6(Ipv6Addr::from(u128::from(a) & self.mask6())),
+        }
+    }
+
+    /// whether `ip` lies within the block. Addresses of the other family never do
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(a), IpAddr::V4(b)) => (u32::from(a) ^ u32::from(b)) & self.mask4() == 0,
+            (IpAddr::V6(a), IpAddr::V6(b)) => (u128::from(a) ^ u128::from(b)) & self.mask6() == 0,
+            _ => false,
+        }
+    }
+
+    fn mask4(&self) -> u32 {
+        match 32u32.checked_sub(self.prefix as u32) {
+            Some(host_bits) => u32::MAX.checked_shl(host_bits).unwrap_or(0),
+            None => u32::MAX,
+        }
+    }
+
+    fn mask6(&self) -> u128 {
+        match 128u32.checked_sub(self.prefix as u32) {
+            Some(host_bits) => u128::MAX.checked_shl(host_bits).unwrap_or(0),
+            None => u128::MAX,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = ParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = whole::<Self>(s, cidr_token)?
+            .split_once('/')
+            .unwrap_or_default();
+        Ok(Cidr {
+            addr: addr
+                .parse()
+                .map_err(|e| ParsingError::conversion::<Self>(s, e))?,
+            prefix: prefix.parse()?,
+        })
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// A 48-bit MAC address as matched by [`mac`], displayed as `00:1a:2b:3c:4d:5e`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl FromStr for MacAddr {
+    type Err = ParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex: Vec<u8> = whole::<Self>(s, mac_token)?
+            .bytes()
+            .filter(u8::is_ascii_hexdigit)
+            .collect();
+        let mut bytes = [0; 6];
+        for (b, pair) in bytes.iter_mut().zip(hex.chunks(2)) {
+            *b = u8::from_str_radix(std::str::from_utf8(pair).unwrap_or_default(), 16)?;
+        }
+        Ok(MacAddr(bytes))
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(f, "{a:02x}:{b:02x}:{c:02x}:{d:02x}:{e:02x}:{g:02x}")
+    }
+}
+
+fn lex<'a, C: Matchable<'a>>(
+    c: C,
+    len: impl FnOnce(&str) -> Option<usize>,
+    action: &'static str,
+) -> C {
+    apply(c, |s| len(s).map(|n| &s[n..]), action, "")
+}
+
+fn whole<T>(s: &str, len: fn(&str) -> Option<usize>) -> Result<&str, ParsingError> {
+    match len(s) {
+        Some(n) if n == s.len() => Ok(s),
+        _ => Err(ParsingError::conversion::<T>(s, "invalid address syntax")),
+    }
+}
+
+// The scanners below return the byte length of a match. Every byte matched is ASCII.
+
+fn run(b: &[u8], pred: impl Fn(&u8) -> bool) -> usize {
+    b.iter().take_while(|c| pred(c)).count()
+}
+
+// nothing more of the word or number follows, such as a letter, or one of `seps`
+// followed by a letter, digit or another separator
+fn ends_word(rest: &str, seps: &[u8]) -> bool {
+    let b = rest.as_bytes();
+    match b.first() {
+        Some(c) if c.is_ascii_alphanumeric() || *c == b'_' => false,
+        Some(c) if seps.contains(c) => {
+            !matches!(b.get(1), Some(d) if d.is_ascii_alphanumeric() || seps.contains(d))
+        }
+        _ => true,
+    }
+}
+
+fn token(s: &str, len: Option<usize>, seps: &[u8]) -> Option<usize> {
+    len.filter(|&n| ends_word(&s[n..], seps))
+}
+
+// a decimal of 1 to `max_len` digits no greater than `max`, without leading zeros
+fn decimal(b: &[u8], max_len: usize, max: u32) -> Option<usize> {
+    let n = run(b, u8::is_ascii_digit);
+    if n == 0 || n > max_len || (n > 1 && b[0] == b'0') {
+        return None;
+    }
+    let value = b[..n].iter().fold(0, |v, d| v * 10 + (d - b'0') as u32);
+    (value <= max).then_some(n)
+}
+
+fn ipv4_len(s: &str) -> Option<usize> {
+    let b = s.as_bytes();
+    let mut i = 0;
+    for octet in 0..4 {
+        if octet > 0 {
+            (b.get(i) == Some(&b'.')).then_some(())?;
+            i += 1;
+        }
+        i += decimal(&b[i..], 3, 255)?;
+    }
+    Some(i)
+}
+
+fn ipv6_len(s: &str) -> Option<usize> {
+    let b = s.as_bytes();
+    let (mut i, mut groups, mut compressed) = (0, 0, false);
+    if b.starts_with(b"::") {
+        (i, compressed) = (2, true);
+    }
+    loop {
+        if compressed && b.get(i) == Some(&b':') {
+            return None;
+        }
+        if let Some(n) = ipv4_len(&s[i..]) {
+            i += n;
+            groups += 2;
+            break;
+        }
+        let n = run(&b[i..], u8::is_ascii_hexdigit);
+        if n == 0 {
+            break;
+        }
+        if n > 4 {
+            return None;
+        }
+        i += n;
+        groups += 1;
+        if groups == 8 {
+            break;
+        }
+        let rest = &b[i..];
+        if rest.starts_with(b"::") && !compressed {
+            i += 2;
+            compressed = true;
+        } else if rest.first() == Some(&b':') && rest.get(1).map_or(false, u8::is_ascii_hexdigit) {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    let complete = if compressed { groups <= 7 } else { groups == 8 };
+    complete.then_some(i)
+}
+
+// `%` and an interface name or number, not ending in `.`
+fn zone_len(s: &str) -> usize {
+    let Some(zone) = s.strip_prefix('%') else {
+        return 0;
+    };
+    let n = run(zone.as_bytes(), |c| {
+        c.is_ascii_alphanumeric() || b"._-~".contains(c)
+    });
+    match zone[..n].trim_end_matches('.').len() {
+        0 => 0,
+        n => n + 1,
+    }
+}
+
+fn ipv4_token(s: &str) -> Option<usize> {
+    token(s, ipv4_len(s), b".")
+}
+
+fn ipv6_token(s: &str) -> Option<usize> {
+    let n = ipv6_len(s)?;
+    token(s, Some(n + zone_len(&s[n..])), b":.")
+}
+
+fn cidr_token(s: &str) -> Option<usize> {
+    let (n, max) = match ipv4_len(s) {
+        Some(n) => (n, 32),
+        None => (ipv6_len(s)?, 128),
+    };
+    let prefix = s[n..].strip_prefix('/')?;
+    let len = n + 1 + decimal(prefix.as_bytes(), 3, max)?;
+    token(s, Some(len), b".")
+}
+
+fn mac_token(s: &str) -> Option<usize> {
+    let b = s.as_bytes();
+    let hex = |i: usize, n: usize| {
+        b.get(i..i + n)
+            .map_or(false, |h| h.iter().all(u8::is_ascii_hexdigit))
+    };
+    let len = if hex(0, 4) && b.get(4) == Some(&b'.') {
+        (hex(5, 4) && b.get(9) == Some(&b'.') && hex(10, 4)).then_some(14)
+    } else {
+        let sep = *b.get(2).filter(|c| b":-".contains(c))?;
+        (0..6)
+            .all(|g| hex(g * 3, 2) && (g == 5 || b.get(g * 3 + 2) == Some(&sep)))
+            .then_some(17)
+    };
+    token(s, len, b":-.")
+}
+
+fn hostname_token(s: &str) -> Option<usize> {
+    let b = s.as_bytes();
+    let mut i = 0;
+    loop {
+        let n = run(&b[i..], |c| c.is_ascii_alphanumeric() || *c == b'-');
+        let label = &b[i..i + n];
+        if n == 0 || n > 63 || label[0] == b'-' || label[n - 1] == b'-' {
+            return None;
+        }
+        i += n;
+        if b.get(i) == Some(&b'.') && b.get(i + 1).map_or(false, u8::is_ascii_alphanumeric) {
+            i += 1;
+        } else if label.iter().all(u8::is_ascii_digit) || i > 253 {
+            return None;
+        } else {
+            return token(s, Some(i), b"");
+        }
+    }
+}
+
+// `[ipv6]`
+fn bracketed(s: &str) -> Option<usize> {
+    let inner = s.strip_prefix('[')?;
+    let n = ipv6_token(inner)?;
+    inner[n..].starts_with(']').then_some(n + 2)
+}
+
+// the host's length plus `:port`
+fn with_port(s: &str, host: usize) -> Option<usize> {
+    let port = s[host..].strip_prefix(':')?;
+    let n = run(port.as_bytes(), u8::is_ascii_digit);
+    let valid = n <= 5 && port[..n].parse::<u16>().is_ok();
+    token(s, valid.then_some(host + 1 + n), b"")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+
+    use super::*;
+    use test_log::test;
+
+    // the text matched by a lexer at the start of `s`
+    fn extent<'a>(lex: fn(Cursor<'a>) -> Cursor<'a>, s: &'a str) -> Option<&'a str> {
+        let rest = lex(Cursor::from(s)).str().ok()?;
+        Some(&s[..s.len() - rest.len()])
+    }
+
+    #[test]
+    fn test_ipv4() {
+        assert_eq!(extent(ipv4, "192.0.2.1"), Some("192.0.2.1"));
+        assert_eq!(extent(ipv4, "0.0.0.0:80"), Some("0.0.0.0"));
+        assert_eq!(extent(ipv4, "10.1.2.3, next"), Some("10.1.2.3"));
+        assert_eq!(extent(ipv4, "10.1.2.3."), Some("10.1.2.3"));
+        assert_eq!(extent(ipv4, "255.255.255.255/"), Some("255.255.255.255"));
+        for s in [
+            "256.1.1.1",
+            "1.2.3",
+            "1.2.3.4.5",
+            "1.2.3.45x",
+            "01.2.3.4",
+            "1.2.3.1000",
+            ".1.2.3.4",
+            "a.b.c.d",
+        ] {
+            assert_eq!(extent(ipv4, s), None, "{s}");
+        }
+
+        let (_, ip) = Cursor::from("127.0.0.1 up")
+            .select(ipv4)
+            .parse_selection::<Ipv4Addr>()
+            .validate()
+            .unwrap();
+        assert_eq!(ip, Ipv4Addr::LOCALHOST);
+    }
+
+    #[test]
+    fn test_ipv6() {
+        for s in [
+            "::",
+            "::1",
+            "1::",
+            "2001:db8::8a2e:370:7334",
+            "2001:0db8:0000:0000:0000:ff00:0042:8329",
+            "1:2:3:4:5:6:7::",
+            "::2:3:4:5:6:7:8",
+            "::ffff:192.0.2.128",
+            "64:ff9b::192.0.2.33",
+            "1:2:3:4:5:6:1.2.3.4",
+        ] {
+            assert_eq!(extent(ipv6, s), Some(s), "{s}");
+            assert_eq!(s.parse::<Ipv6Addr>().is_ok(), true, "{s}");
+            let (_, addr) = Cursor::from(s)
+                .select(ip)
+                .parse_selection::<IpAddr>()
+                .validate()
+                .unwrap();
+            assert_eq!(addr.is_ipv6(), true);
+        }
+        assert_eq!(extent(ipv6, "fe80::1%eth0 up"), Some("fe80::1%eth0"));
+        assert_eq!(extent(ipv6, "fe80::1%eth0.100."), Some("fe80::1%eth0.100"));
+        assert_eq!(extent(ipv6, "fe80::1% up"), Some("fe80::1"));
+        assert_eq!(extent(ipv6, "::1]:80"), Some("::1"));
+        assert_eq!(extent(ipv6, "::1, ::2"), Some("::1"));
+        for s in [
+            "1:2:3:4:5:6:7:8:9",
+            "1::2::3",
+            "12345::",
+            ":::",
+            ":1::",
+            "1:2:3:4:5:6:7",
+            "1:2:3:4:5:6:7:8::",
+            "::1.2.3",
+            "::1x",
+            "1:2:3:4:5:6:7:1.2.3.4",
+            "g::1",
+        ] {
+            assert_eq!(extent(ipv6, s), None, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_cidr_and_mac() {
+        assert_eq!(extent(cidr, "10.0.0.0/8 ok"), Some("10.0.0.0/8"));
+        assert_eq!(extent(cidr, "2001:db8::/32"), Some("2001:db8::/32"));
+        assert_eq!(extent(cidr, "::/0"), Some("::/0"));
+        for s in [
+            "10.0.0.0/33",
+            "10.0.0.0/08",
+            "10.0.0.0",
+            "::/129",
+            "10.0.0.0/8a",
+        ] {
+            assert_eq!(extent(cidr, s), None, "{s}");
+        }
+        let block: Cidr = "192.168.1.77/24".parse().unwrap();
+        assert_eq!(block.network(), "192.168.1.0".parse::<IpAddr>().unwrap());
+        assert_eq!(block.contains("192.168.1.200".parse().unwrap()), true);
+        assert_eq!(block.contains("192.168.2.1".parse().unwrap()), false);
+        assert_eq!(block.contains("::1".parse().unwrap()), false);
+        assert_eq!(block.to_string(), "192.168.1.77/24");
+        let all: Cidr = "0.0.0.0/0".parse().unwrap();
+        assert_eq!(all.contains("8.8.8.8".parse().unwrap()), true);
+        let v6: Cidr = "2001:db8::/32".parse().unwrap();
+        assert_eq!(v6.contains("2001:db8:ffff::1".parse().unwrap()), true);
+        assert_eq!(v6.contains("2001:db9::1".parse().unwrap()), false);
+        assert_eq!("10.0.0.0/8 ".parse::<Cidr>().is_err(), true);
+        // a prefix over the address length, only possible by hand, covers just the address
+        let addr = "10.1.2.3".parse().unwrap();
+        let host = Cidr { addr, prefix: 40 };
+        assert_eq!(host.network(), addr);
+        assert_eq!(host.contains(addr), true);
+        assert_eq!(host.contains("10.1.2.4".parse().unwrap()), false);
+        let addr = "::1".parse().unwrap();
+        assert_eq!(Cidr { addr, prefix: 200 }.network(), addr);
+
+        let mac_addr = MacAddr([0x00, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e]);
+        for s in ["00:1a:2b:3c:4d:5e", "00-1A-2B-3C-4D-5E", "001a.2b3c.4d5e"] {
+            assert_eq!(extent(mac, s), Some(s), "{s}");
+            assert_eq!(s.parse::<MacAddr>().unwrap(), mac_addr);
+        }
+        assert_eq!(
+            extent(mac, "00:1a:2b:3c:4d:5e: up"),
+            Some("00:1a:2b:3c:4d:5e")
+        );
+        for s in [
+            "00:1a:2b:3c:4d",
+            "00:1a-2b:3c:4d:5e",
+            "00:1a:2b:3c:4d:5e:6f",
+            "00:1a:2b:3c:4d:5g",
+        ] {
+            assert_eq!(extent(mac, s), None, "{s}");
+        }
+        assert_eq!(mac_addr.to_string(), "00:1a:2b:3c:4d:5e");
+    }
+
+    #[test]
+    fn test_host_port() {
+        let socket = |s| {
+            Cursor::from(s)
+                .select(socket_addr)
+                .parse_selection::<SocketAddr>()
+                .validate()
+                .map(|(_, a)| a)
+        };
+        assert_eq!(
+            socket("10.0.0.1:8080").unwrap(),
+            "10.0.0.1:8080".parse().unwrap()
+        );
+        assert_eq!(socket("[::1]:443").unwrap().port(), 443);
+        assert_eq!(
+            socket("[fe80::1%2]:22").unwrap().to_string(),
+            "[fe80::1%2]:22"
+        );
+        assert_eq!(
+            extent(socket_addr, "[fe80::1%eth0]:22"),
+            Some("[fe80::1%eth0]:22")
+        );
+        for s in [
+            "10.0.0.1",
+            "10.0.0.1:",
+            "10.0.0.1:65536",
+            "10.0.0.1:80x",
+            "::1:80",
+            "[::1]",
+            "host:80",
+        ] {
+            assert_eq!(extent(socket_addr, s), None, "{s}");
+        }
+
+        assert_eq!(
+            extent(host_port, "example.com:443/"),
+            Some("example.com:443")
+        );
+        assert_eq!(extent(host_port, "localhost:8080"), Some("localhost:8080"));
+        assert_eq!(extent(host_port, "10.0.0.1:53"), Some("10.0.0.1:53"));
+        assert_eq!(
+            extent(host_port, "[2001:db8::1]:80"),
+            Some("[2001:db8::1]:80")
+        );
+        for s in [
+            "example.com",
+            "-bad.com:80",
+            "bad-.com:80",
+            "10.0.0.256:80",
+            "a..b:80",
+        ] {
+            assert_eq!(extent(host_port, s), None, "{s}");
+        }
+
+        assert_eq!(
+            extent(hostname, "db-1.internal. Next"),
+            Some("db-1.internal")
+        );
+        assert_eq!(
+            extent(hostname, "xn--bcher-kva.example"),
+            Some("xn--bcher-kva.example")
+        );
+        assert_eq!(extent(hostname, "123"), None);
+        assert_eq!(extent(hostname, &"a".repeat(64)), None);
+    }
+
+    #[test]
+    fn test_log_line() {
+        let line = "Oct 19 sshd[812]: Failed password from 203.0.113.9 port 51022 on fe80::a00:27ff:fe4e:66a1%enp0s3";
+        let (_, from, port, on) = Cursor::from(line)
+            .find("from ")
+            .text("from ")
+            .select(ip)
+            .parse_selection::<IpAddr>()
+            .text(" port ")
+            .select(|c| c.digits(1..))
+            .parse_selection::<u16>()
+            .text(" on ")
+            .select(ipv6)
+            .parse_selection_as_str()
+            .end_of_stream()
+            .validate()
+            .unwrap();
+        assert_eq!(from, IpAddr::from([203, 0, 113, 9]));
+        assert_eq!(port, 51022);
+        assert_eq!(on.split_once('%').unwrap().1, "enp0s3");
+    }
+}
@@ -0,0 +1,331 @@
+//! URIs, following the generic syntax of [RFC 3986](https://www.rfc-editor.org/rfc/rfc3986#section-3).
+//!
+//! A [`Uri`] borrows each of its parts from the input, without decoding `%` escapes.
+//! Relative references such as `../a` have no scheme and are not URIs.
+//!
+//! ```
+//! use daisychain::contrib::uri;
+//! use daisychain::prelude::*;
+//!
+//! let (_, u) = uri::uri("https://andy@example.com:8443/a/b?x=1&y=%20#top").unwrap();
+//! assert_eq!(u.scheme, "https");
+//! let authority = u.authority.unwrap();
+//! assert_eq!(authority.userinfo, Some("andy"));
+//! assert_eq!(authority.host, "example.com");
+//! assert_eq!(authority.port, Some("8443"));
+//! assert_eq!((u.path, u.query, u.fragment), ("/a/b", Some("x=1&y=%20"), Some("top")));
+//!
+//! let (_, u) = uri::uri("mailto:andy@example.com").unwrap();
+//! assert_eq!((u.authority, u.path), (None, "andy@example.com"));
+//!
+//! let (_, link) = Cursor::from("see <http://[2001:db8::1]/docs> for more")
+//!     .find("<")
+//!     .text("<")
+//!     .select(uri::lex_uri)
+//!     .parse_selection_as_str()
+//!     .text(">")
+//!     .validate()
+//!     .unwrap();
+//! assert_eq!(link, "http://[2001:db8::1]/docs");
+//! ```
+use std::{fmt, net::Ipv6Addr};
+
+use crate::prelude::*;
+use crate::text_parser::apply;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Uri<'a> {
+    pub scheme: &'a str,
+    /// present when the scheme is followed by `//`
+    pub authority: Option<Authority<'a>>,
+    /// possibly empty, and beginning with `/` when there is an authority
+    pub path: &'a str,
+    /// after the `?`
+    pub query: Option<&'a str>,
+    /// after the `#`
+    pub fragment: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Authority<'a> {
+    /// before the `@`
+    pub userinfo: Option<&'a str>,
+    /// a registered name, IPv4 address, or IPv6 address in brackets. May be empty
+    pub host: &'a str,
+    /// digits after the `:`, which RFC 3986 allows to be empty or out of range
+    pub port: Option<&'a str>,
+}
+
+impl fmt::Display for Uri<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:", self.scheme)?;
+        if let Some(authority) = &self.authority {
+            write!(f, "//{authority}")?;
+        }
+        write!(f, "{}", self.path)?;
+        if let Some(query) = self.query {
+            write!(f, "?{query}")?;
+        }
+        if let Some(fragment) = self.fragment {
+            write!(f, "#{fragment}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Authority<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(userinfo) = self.userinfo {
+            write!(f, "{userinfo}@")?;
+        }
+        write!(f, "{}", self.host)?;
+        if let Some(port) = self.port {
+            write!(f, ":{port}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A URI, as a stir-style parser. Parsing stops at the first character a URI cannot contain
+pub fn uri(s: &str) -> Result<(&str, Uri<'_>), ParsingError> {
+    let (rest, scheme) = Cursor::from(s)
+        .debug_context("uri")
+        .select(scheme)
+        .parse_selection_as_str()
+        .text(":")
+        .validate()?;
+    let (rest, authority) = match rest.strip_prefix("//") {
+        Some(rest) => authority(rest).map(|(rest, a)| (rest, Some(a)))?,
+        None => (rest, None),
+    };
+    // after an authority, the path is empty or begins with `/`
+    let (rest, path) = match authority {
+        Some(_) if !rest.starts_with('/') => (rest, ""),
+        _ => Cursor::from(rest)
+            .select(|c| encoded(c, |ch| is_pchar(ch) || ch == '/'))
+            .parse_selection_as_str()
+            .validate()?,
+    };
+    let (rest, query) = part(rest, '?')?;
+    let (rest, fragment) = part(rest, '#')?;
+    Ok((
+        rest,
+        Uri {
+            scheme,
+            authority,
+            path,
+            query,
+            fragment,
+        },
+    ))
+}
+
+/// Selects a URI, with the same rules as [`uri`]
+pub fn lex_uri<'a, C: Matchable<'a>>(c: C) -> C {
+    apply(c, |s| uri(s).ok().map(|(rest, _)| rest), "uri", "")
+}
+
+fn authority(s: &str) -> Result<(&str, Authority<'_>), ParsingError> {
+    let (rest, userinfo) = match s.find(['@', '/', '?', '#']) {
+        Some(i) if s[i..].starts_with('@') => Cursor::from(s)
+            .debug_context("userinfo")
+            .select(|c| encoded(c, |ch| is_unreserved(ch) || is_sub_delim(ch) || ch == ':'))
+            .parse_selection_as_str()
+            .text("@")
+            .validate()
+            .map(|(rest, u)| (rest, Some(u)))?,
+        _ => (s, None),
+    };
+    let (rest, host) = Cursor::from(rest)
+        .debug_context("host")
+        .select(host)
+        .parse_selection_as_str()
+        .try_map_last(check_host)
+        .validate()?;
+    let (rest, port) = match rest.strip_prefix(':') {
+        Some(rest) => Cursor::from(rest)
+            .chars_match(0.., |ch| ch.is_ascii_digit())
+            .parse_selection_as_str()
+            .validate()
+            .map(|(rest, port)| (rest, Some(port)))?,
+        None => (rest, None),
+    };
+    Ok((
+        rest,
+        Authority {
+            userinfo,
+            host,
+            port,
+        },
+    ))
+}
+
+// `?query` or `#fragment`, if present
+fn part(s: &str, delim: char) -> Result<(&str, Option<&str>), ParsingError> {
+    let Some(rest) = s.strip_prefix(delim) else {
+        return Ok((s, None));
+    };
+    let (rest, text) = Cursor::from(rest)
+        .select(|c| encoded(c, |ch| is_pchar(ch) || ch == '/' || ch == '?'))
+        .parse_selection_as_str()
+        .validate()?;
+    Ok((rest, Some(text)))
+}
+
+fn scheme<'a, C: Matchable<'a>>(c: C) -> C {
+    c.chars_match(1..=1, |ch| ch.is_ascii_alphabetic())
+        .chars_match(0.., |ch| ch.is_ascii_alphanumeric() || "+-.".contains(ch))
+}
+
+// an IP literal such as `[::1]` or `[v1.x]`, or a registered name, which includes IPv4
+fn host<'a, C: Matchable<'a> + Clone>(c: C) -> C {
+    match c.str() {
+        Ok(s) if s.starts_with('[') => c
+            .text("[")
+            .chars_match(1.., |ch| ch != ']' && !ch.is_whitespace())
+            .text("]"),
+        _ => encoded(c, |ch| is_unreserved(ch) || is_sub_delim(ch)),
+    }
+}
+
+fn check_host(host: &str) -> Result<&str, String> {
+    let Some(s) = host.strip_prefix('[').and_then(|s| s.strip_suffix(']')) else {
+        return Ok(host);
+    };
+    let future = s.strip_prefix(['v', 'V']).and_then(|s| s.split_once('.'));
+    match future {
+        Some((version, text)) => {
+            let valid = !version.is_empty()
+                && version.chars().all(|ch| ch.is_ascii_hexdigit())
+                && !text.is_empty()
+                && text
+                    .chars()
+                    .all(|ch| is_unreserved(ch) || is_sub_delim(ch) || ch == ':');
+            valid
+                .then_some(host)
+                .ok_or(format!("invalid IPvFuture '{s}'"))
+        }
+        None => s
+            .parse::<Ipv6Addr>()
+            .map(|_| host)
+            .map_err(|e| format!("invalid IPv6 address '{s}': {e}")),
+    }
+}
+
+// characters allowed by `pred`, and %-encoded octets
+fn encoded<'a, C: Matchable<'a> + Clone>(c: C, pred: fn(char) -> bool) -> C {
+    c.repeat(0.., |c| match c.str() {
+        Ok(s) if s.starts_with('%') => c.text("%").chars_match(2..=2, |ch| ch.is_ascii_hexdigit()),
+        _ => c.chars_match(1.., pred),
+    })
+}
+
+fn is_unreserved(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || "-._~".contains(ch)
+}
+
+fn is_sub_delim(ch: char) -> bool {
+    "!$&'()*+,;=".contains(ch)
+}
+
+fn is_pchar(ch: char) -> bool {
+    is_unreserved(ch) || is_sub_delim(ch) || ch == ':' || ch == '@'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn test_uri() {
+        // the examples of RFC 3986 section 1.1.2
+        for s in [
+            "ftp://ftp.is.co.za/rfc/rfc1808.txt",
+            "http://www.ietf.org/rfc/rfc2396.txt",
+            "ldap://[2001:db8::7]/c=GB?objectClass?one",
+            "mailto:John.Doe@example.com",
+            "news:comp.infosystems.www.servers.unix",
+            "tel:+1-816-555-1212",
+            "telnet://192.0.2.16:80/",
+            "urn:oasis:names:specification:docbook:dtd:xml:4.1.2",
+            "foo://example.com:8042/over/there?name=ferret#nose",
+            "file:///etc/hosts",
+            "http://[v7.fe80::a+en1]",
+            "s3+v2.x://:@:/?#",
+        ] {
+            let (rest, u) = uri(s).unwrap_or_else(|e| panic!("{s}: {e}"));
+            assert_eq!((rest, u.to_string()), ("", s.to_string()));
+        }
+
+        let (_, u) = uri("ldap://[2001:db8::7]/c=GB?objectClass?one").unwrap();
+        assert_eq!(u.authority.unwrap().host, "[2001:db8::7]");
+        assert_eq!(
+            (u.path, u.query, u.fragment),
+            ("/c=GB", Some("objectClass?one"), None)
+        );
+
+        let (_, u) = uri("file:///etc/hosts").unwrap();
+        assert_eq!(u.authority.map(|a| a.host), Some(""));
+        assert_eq!(u.path, "/etc/hosts");
+
+        let (_, u) = uri("urn:isbn:0451450523").unwrap();
+        assert_eq!(
+            (u.scheme, u.authority, u.path),
+            ("urn", None, "isbn:0451450523")
+        );
+
+        let (_, u) = uri("http://a%20b@h:/p%2Fq#").unwrap();
+        let authority = u.authority.unwrap();
+        assert_eq!(
+            (authority.userinfo, authority.port),
+            (Some("a%20b"), Some(""))
+        );
+        assert_eq!((u.path, u.fragment), ("/p%2Fq", Some("")));
+
+        for s in [
+            "",
+            "1http://x",
+            "://x",
+            "http//x",
+            "http://[::g]/",
+            "http://[::1",
+            "http://[vz.1]",
+        ] {
+            assert_eq!(uri(s).is_err(), true, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_lex_uri() {
+        let extent = |s: &'static str| {
+            let rest = lex_uri(Cursor::from(s)).str().ok()?;
+            Some(&s[..s.len() - rest.len()])
+        };
+        assert_eq!(
+            extent("https://example.com/a b"),
+            Some("https://example.com/a")
+        );
+        assert_eq!(
+            extent("https://example.com/\"quoted\""),
+            Some("https://example.com/")
+        );
+        assert_eq!(
+            extent("https://example.com:443x"),
+            Some("https://example.com:443")
+        );
+        assert_eq!(extent("http://x/%zz"), Some("http://x/"));
+        assert_eq!(extent("http://x/a|b"), Some("http://x/a"));
+        assert_eq!(extent("no scheme here"), None);
+
+        let line = r#"GET "https://api.example.com/v1/items?id=7" 200"#;
+        let (_, u) = Cursor::from(line)
+            .text("GET \"")
+            .parse_with(uri)
+            .text("\" ")
+            .validate()
+            .unwrap();
+        assert_eq!(u.authority.unwrap().host, "api.example.com");
+        assert_eq!(u.query, Some("id=7"));
+    }
+}
@@ -46,6 +46,21 @@ fn parse_quoted_text_v2(inp: &str) -> Result<(&str, QuotedText), ParsingError> {
     Ok((c, QuotedText { quote, text }))
 }
 
+/// alternative implementation assigning directly to struct fields.
+/// Fields are assigned by name, so reordering the chain cannot swap them over
+///
+fn parse_quoted_text_v3(inp: &str) -> Result<(&str, QuotedText), ParsingError> {
+    let mut qt = QuotedText::new(char::default(), String::new());
+    let c = Cursor::from(inp)
+        .chars_in(1..=1, &['"', '\''])
+        .parse_selection_into(&mut qt.quote)
+        .chars_not_in(0.., &[qt.quote])
+        .parse_selection_into(&mut qt.text)
+        .chars_in(1..=1, &[qt.quote])
+        .validate()?;
+    Ok((c, qt))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,6 +81,11 @@ mod tests {
         assert_eq!(qt, QuotedText::new('"', "Hi".to_string()));
         assert_eq!(c, ", he said");
 
+        let (c, qt) = parse_quoted_text_v3("\"Hi\", he said")?;
+        assert_eq!(qt, QuotedText::new('"', "Hi".to_string()));
+        assert_eq!(c, ", he said");
+        assert!(parse_quoted_text_v3("'Hi, ").is_err());
+
         let res = parse_quoted_text("'Hi, ");
         assert!(res.is_err());
         Ok(())
@@ -22,7 +22,9 @@ fn parse_number(s: &str) -> Result<(&str, Number), ParsingError> {
         return Ok((c, Number::Binary(u32::from_str_radix(s, 2)?)));
     }
 
-    // try second variant (using clone to save the initial cursor position)
+    // try second variant (using clone to save the initial cursor position).
+    // Unlike `?` above, try_map_last rejects a value with a recoverable error, so a hex
+    // number too large for a u32 falls through to the next variant
     let hex_chars = b"0123456789ABCDEF".map(|c| c as char);
     if let Ok((c, hex)) = Cursor::from(s)
         .clone()
@@ -30,9 +32,10 @@ fn parse_number(s: &str) -> Result<(&str, Number), ParsingError> {
         .text("0x")
         .chars_in(1.., hex_chars.as_slice())
         .parse_selection_as_str()
+        .try_map_last(|hex| u32::from_str_radix(hex, 16))
         .validate()
     {
-        return Ok((c, Number::Hex(u32::from_str_radix(hex, 16)?)));
+        return Ok((c, Number::Hex(hex)));
     }
 
     // try third variant - no need to clone
@@ -68,5 +71,13 @@ mod tests {
         let (c, var) = parse_number("0b201").unwrap();
         assert_eq!(var, Number::Decimal(0));
         assert_eq!(c, "b201");
+
+        // too large for a u32, which is fatal rather than a reason to try the next variant
+        assert!(parse_number("0b111100001111000011110000111100001").is_err());
+
+        // whereas a hex number too large is rejected by try_map_last, and decimal is tried
+        let (c, var) = parse_number("0x1FFFFFFFF").unwrap();
+        assert_eq!(var, Number::Decimal(0));
+        assert_eq!(c, "x1FFFFFFFF");
     }
 }
@@ -4,10 +4,6 @@ use crate::cookbook::{ch_2_simple_example::Time, ch_7_alternate::parse_clock};
 use crate::prelude::Cursor;
 use crate::prelude::*;
 
-///
-///
-///
-///
 #[derive(Debug, PartialEq)]
 struct TimePeriod(Time, Time);
 
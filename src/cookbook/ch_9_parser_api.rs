@@ -59,11 +59,11 @@ fn parse_str_money(s: &str) -> Result<(&str, Money), ParsingError> {
 //     Ok((c, Money(float)))
 // }
 
-///
-/// Stir-style (associated-function or method) parser:
-///
-/// the function takes a Cursor and returns a Result of (Cursor, T)
-///
+//
+// Stir-style (associated-function or method) parser:
+//
+// the function takes a Cursor and returns a Result of (Cursor, T)
+//
 
 // eg MoneyParser("$".to_string())
 struct MoneyParser {
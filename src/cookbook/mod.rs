@@ -67,10 +67,12 @@ A composition pattern is typical, with small parsers handling constituent object
 For test harnesses and experimentation, Option<&str> is a simple Cursor (without the ability to select text), but is useful for test harnesses. 
 None is used to represent a matching issue. 
 
-For more substantial tests during development of your parsers, using a logging framework for test-harnesses is encoraged
+For more substantial tests during development of your parsers, using a logging framework for test-harnesses is encoraged.
+Tracing is compiled out unless the `trace` feature is enabled, so enable it for tests only
 
 ```toml
 [dev-dependencies]
+daisychain = {version = "0.0.5", features = ["trace"]}
 env_logger = "0.9"
 test-log = {version = "0.2"}
 ```
@@ -80,6 +82,16 @@ This will allow the action of parsing to be traced with
 RUST_LOG=dc=trace cargo test mytest -- --nocapture
 ```
 
+Alternatively a parse can be recorded and written as a standalone HTML report, with the
+input shown at each step and collapsible rules and `debug_context` sections
+```rust,ignore
+use daisychain::trace::{html_report, with_tracer, Recorder};
+
+let rec = Recorder::new();
+let res = with_tracer(rec.clone(), || parse_my_struct(input));
+std::fs::write("trace.html", html_report(input, &rec.records()))?;
+```
+
 */
 
 pub mod ch_1_getting_started;
@@ -2,7 +2,7 @@ use std::fmt;
 
 use crate::logging::Loggable;
 use crate::prelude::Matchable;
-use crate::{prelude::ParsingError, util};
+use crate::{context, limits, prelude::ParsingError, span::Origin, util};
 
 #[derive(Debug, Clone)]
 pub struct Cursor<'a> {
@@ -10,6 +10,12 @@ pub struct Cursor<'a> {
     pub(crate) cur: Option<&'a str>,
     pub(crate) err: Option<ParsingError>,
     pub(crate) context: &'static str,
+    pub(crate) origin: Origin,
+    pub(crate) captures: Captures<'a>,
+    /// where the cursor was when it failed
+    pub(crate) failed_at: Option<&'a str>,
+    /// where its debug_context label goes on the context stack
+    pub(crate) context_base: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -19,11 +25,51 @@ pub enum Selection<'a> {
     Last(&'a str, &'a str),
 }
 
+/// Named selections ("registers"), which unlike the selection itself,
+/// survive subsequent matching. Numbered in the order first captured.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Captures<'a> {
+    named: Vec<(&'static str, &'a str)>,
+}
+
+pub(crate) static NO_CAPTURES: Captures<'static> = Captures { named: Vec::new() };
+
+impl<'a> Captures<'a> {
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.named.iter().find(|(n, _)| *n == name).map(|(_, text)| *text)
+    }
+
+    pub fn nth(&self, i: usize) -> Option<&'a str> {
+        self.named.get(i).map(|(_, text)| *text)
+    }
+
+    pub fn len(&self) -> usize {
+        self.named.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.named.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &'a str)> + '_ {
+        self.named.iter().copied()
+    }
+
+    /// recapturing a name replaces its text, but keeps its number
+    pub(crate) fn insert(&mut self, name: &'static str, text: &'a str) {
+        match self.named.iter_mut().find(|(n, _)| *n == name) {
+            Some(entry) => entry.1 = text,
+            None => self.named.push((name, text)),
+        }
+    }
+}
+
 // equal and error free
 impl<'a> PartialEq for Cursor<'a> {
     #[allow(clippy::match_like_matches_macro)]
     fn eq(&self, other: &Self) -> bool {
         self.selection == other.selection
+            && self.captures == other.captures
             && self.cur == other.cur
             && self.context == other.context
             && match (&self.err, &other.err) {
@@ -41,7 +87,15 @@ impl<'a> From<&'a str> for Cursor<'a> {
             cur: Some(s),
             err: None,
             context: "",
+            origin: limits::origin_of(s),
+            captures: Captures::default(),
+            failed_at: None,
+            context_base: context::base(),
         };
+        if let Err(e) = limits::check_input_len(s) {
+            cur.log_failure("Cursor::from", "", &e);
+            return cur.set_error(e);
+        }
         cur.log_success("Cursor::from", "");
         cur
     }
@@ -0,0 +1,398 @@
+use std::{
+    fmt::{self, Write},
+    ops::Bound,
+};
+
+/// A description of a parser (or one step of it), for documenting the syntax a
+/// parser accepts. `kind` is the method or combinator name, eg "digits", `args` are
+/// its arguments, and `children` any nested parsers (eg of a "select").
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub kind: &'static str,
+    pub args: Vec<Arg>,
+    pub children: Vec<Node>,
+}
+
+/// An argument of a parser step, kept structured so that each rendering can quote it
+/// as it needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    /// text to be matched, eg by `text` or `find`
+    Text(String),
+    /// the number of chars (or repeats) matched, eg `1..=2`
+    Range(Bound<i32>, Bound<i32>),
+    /// the chars of eg `chars_in`
+    Chars(Vec<char>),
+    /// a type, parser function or `debug_context` label
+    Name(String),
+}
+
+impl fmt::Display for Arg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bound = |b: &Bound<i32>| match b {
+            Bound::Included(i) | Bound::Excluded(i) => i.to_string(),
+            Bound::Unbounded => String::new(),
+        };
+        match self {
+            Self::Text(text) => write!(f, "{text:?}"),
+            Self::Range(start, end) => {
+                let dots = match end {
+                    Bound::Included(_) => "..=",
+                    _ => "..",
+                };
+                write!(f, "{}{dots}{}", bound(start), bound(end))
+            }
+            Self::Chars(chars) => write!(f, "{chars:?}"),
+            Self::Name(name) => f.write_str(name),
+        }
+    }
+}
+
+/// Parsers and combinators which can describe themselves
+pub trait Describe {
+    fn describe(&self) -> Node;
+}
+
+impl Node {
+    pub fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            args: vec![],
+            children: vec![],
+        }
+    }
+
+    pub fn arg(mut self, arg: Arg) -> Self {
+        self.args.push(arg);
+        self
+    }
+
+    pub fn child(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// a sequence of steps, with any nested sequences flattened into it
+    pub fn seq(children: impl IntoIterator<Item = Node>) -> Self {
+        let mut seq = Node::new("seq");
+        for child in children {
+            match child.kind {
+                "seq" => seq.children.extend(child.children),
+                _ => seq.children.push(child),
+            }
+        }
+        seq
+    }
+
+    fn label(&self) -> String {
+        std::iter::once(self.kind.to_string())
+            .chain(self.args.iter().map(Arg::to_string))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// an indented tree, one node per line
+    pub fn to_text(&self) -> String {
+        let mut s = self.label();
+        s.push('\n');
+        self.write_tree(&mut s, "");
+        s
+    }
+
+    fn write_tree(&self, s: &mut String, indent: &str) {
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i + 1 == self.children.len();
+            let (branch, cont) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            let _ = writeln!(s, "{indent}{branch}{}", child.label());
+            child.write_tree(s, &format!("{indent}{cont}"));
+        }
+    }
+
+    /// a Graphviz digraph, rendered with eg `dot -Tsvg`
+    pub fn to_dot(&self) -> String {
+        let mut s = String::from("digraph parser {\n    node [shape=box];\n");
+        self.write_dot(&mut s, &mut 0);
+        s.push_str("}\n");
+        s
+    }
+
+    fn write_dot(&self, s: &mut String, next_id: &mut usize) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        let label = self.label().replace('\\', "\\\\").replace('"', "\\\"");
+        let _ = writeln!(s, "    n{id} [label=\"{label}\"];");
+        for child in &self.children {
+            let child_id = child.write_dot(s, next_id);
+            let _ = writeln!(s, "    n{id} -> n{child_id};");
+        }
+        id
+    }
+
+    /// an EBNF-like rule, `name = ... ;`. Captured values are shown as comments,
+    /// and stir-style parsers (`parse_with`) as references to other rules
+    pub fn to_ebnf(&self, name: &str) -> String {
+        format!("{name} = {} ;", self.ebnf_expr())
+    }
+
+    fn ebnf_expr(&self) -> String {
+        let range = || {
+            self.args
+                .iter()
+                .find_map(|arg| match arg {
+                    Arg::Range(start, end) => Some((*start, *end)),
+                    _ => None,
+                })
+                .unwrap_or((Bound::Unbounded, Bound::Unbounded))
+        };
+        let group = |alts: Vec<String>| match alts.len() {
+            1 => alts.into_iter().next().unwrap_or_default(),
+            _ => format!("( {} )", alts.join(" | ")),
+        };
+        let chars = || {
+            self.args
+                .iter()
+                .flat_map(|arg| match arg {
+                    Arg::Chars(chars) => chars.as_slice(),
+                    _ => &[],
+                })
+                .map(|c| terminal(&c.to_string()))
+                .collect::<Vec<_>>()
+        };
+        let texts = || {
+            self.args
+                .iter()
+                .map(|arg| match arg {
+                    Arg::Text(text) => terminal(text),
+                    arg => arg.to_string(),
+                })
+                .collect::<Vec<_>>()
+        };
+        match self.kind {
+            "seq" => {
+                let items: Vec<_> = self
+                    .children
+                    .iter()
+                    .map(|c| c.ebnf_expr())
+                    .filter(|e| !e.is_empty())
+                    .collect();
+                match items.len() {
+                    0 => "\"\"".to_string(),
+                    _ => items.join(" , "),
+                }
+            }
+            "text" | "char" => texts().join(""),
+            "maybe" => format!("[ {} ]", texts().join("")),
+            "text_alt" => group(texts()),
+            "alt" => group(self.children.iter().map(|c| c.ebnf_expr()).collect()),
+            "select" => format!("( {} )", Node::seq(self.children.clone()).ebnf_expr()),
+            "repeat" => repetition(
+                &format!("( {} )", Node::seq(self.children.clone()).ebnf_expr()),
+                range(),
+            ),
+            "digits" => repetition("digit", range()),
+            "alphabetics" => repetition("letter", range()),
+            "alphanumerics" => repetition("alnum", range()),
+            "chars_any" => repetition("? any char ?", range()),
+            "chars_match" => repetition("? matching char ?", range()),
+            "chars_in" => repetition(&group(chars()), range()),
+            "chars_not_in" => repetition(
+                &format!("? any char except {} ?", chars().join(" ")),
+                range(),
+            ),
+            "ws" => "ws".to_string(),
+            "hws" => "hws".to_string(),
+            "non_ws" => "{ ? non-whitespace char ? }".to_string(),
+            "word" => "word".to_string(),
+            "end_of_line" => "eol".to_string(),
+            "end_of_stream" => "eos".to_string(),
+            "scan_eol" => "{ ? any char ? } , eol".to_string(),
+            "find" | "scan_text" => format!("{{ ? any char ? }} , {}", texts().join("")),
+            "parse_selection" | "parse_selection_as_str" => match self.args.first() {
+                Some(arg) => format!("(* {arg} *)"),
+                None => "(* &str *)".to_string(),
+            },
+            "parse_with" | "lexer" => match self.args.first() {
+                Some(arg) => arg.to_string(),
+                None => format!("? {} ?", self.kind),
+            },
+            // selection_start, selection_end, debug_context etc do not consume input
+            _ => String::new(),
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_text())
+    }
+}
+
+/// an EBNF-like listing of several named rules
+pub fn ebnf<'r>(rules: impl IntoIterator<Item = (&'r str, &'r dyn Describe)>) -> String {
+    rules
+        .into_iter()
+        .map(|(name, parser)| parser.describe().to_ebnf(name) + "\n")
+        .collect()
+}
+
+// an EBNF terminal, in whichever quotes it does not contain
+fn terminal(text: &str) -> String {
+    match text.contains('"') {
+        true => format!("'{text}'"),
+        false => format!("\"{text}\""),
+    }
+}
+
+// 2..=2 -> 2 * digit, 1.. -> digit , { digit }
+fn repetition(item: &str, (start, end): (Bound<i32>, Bound<i32>)) -> String {
+    let count = |i: i32| usize::try_from(i).unwrap_or(0);
+    let lo = match start {
+        Bound::Included(i) => count(i),
+        Bound::Excluded(i) => count(i) + 1,
+        Bound::Unbounded => 0,
+    };
+    let hi = match end {
+        Bound::Included(i) => Some(count(i)),
+        Bound::Excluded(i) => Some(count(i).saturating_sub(1)),
+        Bound::Unbounded => None,
+    };
+    let times = |n: usize| match n {
+        1 => item.to_string(),
+        n => format!("{n} * {item}"),
+    };
+    let required = (lo > 0).then(|| times(lo));
+    let optional = match hi {
+        None => Some(format!("{{ {item} }}")),
+        Some(hi) if hi == lo + 1 => Some(format!("[ {item} ]")),
+        Some(hi) if hi > lo => Some(format!("{} * [ {item} ]", hi - lo)),
+        Some(_) => None,
+    };
+    match (required, optional) {
+        (Some(r), Some(o)) => format!("{r} , {o}"),
+        (Some(r), None) => r,
+        (None, Some(o)) => o,
+        (None, None) => "\"\"".to_string(),
+    }
+}
+
+/// the type name without module paths, eg `Vec<String>` or `{{closure}}`
+pub(crate) fn short_type_name(type_name: &str) -> String {
+    let is_path = |c: char| c.is_alphanumeric() || c == '_' || c == ':';
+    let mut s = String::new();
+    for part in type_name.split_inclusive(|c: char| !is_path(c)) {
+        let (path, delim) = part.split_at(part.trim_end_matches(|c: char| !is_path(c)).len());
+        s.push_str(path.rsplit("::").next().unwrap_or(path));
+        s.push_str(delim);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::lazy::{Lazy, Seq};
+    use crate::prelude::ParsingError;
+    use test_log::test;
+
+    fn time(s: &str) -> Result<(&str, u32), ParsingError> {
+        Ok((s, 0))
+    }
+
+    fn event() -> impl Describe {
+        Seq::new()
+            .ws()
+            .text_alt(&["at", "@"])
+            .hws()
+            .parse_with(time)
+            .maybe("!")
+            .select(|s| s.chars_in(0..=2, &['a', 'p']).text("m"))
+            .parse_selection_as_str()
+            .alt([Seq::new().digits(2..=2), Seq::new().alphabetics(1..)])
+    }
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(short_type_name("alloc::string::String"), "String");
+        assert_eq!(
+            short_type_name("core::option::Option<alloc::vec::Vec<u8>>"),
+            "Option<Vec<u8>>"
+        );
+        assert_eq!(
+            short_type_name("daisychain::describe::tests::{{closure}}"),
+            "{{closure}}"
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let text = event().describe().to_text();
+        let expected = r#"seq
+├── ws
+├── text_alt "at" "@"
+├── hws
+├── parse_with time
+├── maybe "!"
+├── select
+│   ├── chars_in 0..=2 ['a', 'p']
+│   └── text "m"
+├── parse_selection_as_str &str
+└── alt
+    ├── seq
+    │   └── digits 2..=2
+    └── seq
+        └── alphabetics 1..
+"#;
+        assert_eq!(text, expected, "\n{text}");
+    }
+
+    #[test]
+    fn test_dot() {
+        let dot = Seq::new().text("\"").digits(1..).describe().to_dot();
+        let expected = r#"digraph parser {
+    node [shape=box];
+    n0 [label="seq"];
+    n1 [label="text \"\\\"\""];
+    n0 -> n1;
+    n2 [label="digits 1.."];
+    n0 -> n2;
+}
+"#;
+        assert_eq!(dot, expected, "\n{dot}");
+    }
+
+    #[test]
+    fn test_ebnf() {
+        let list = ebnf([
+            ("event", &event() as &dyn Describe),
+            ("time", &Seq::new().digits(1..=2).text(":").digits(2..=2)),
+            ("hex", &Seq::new().text("0x").chars_any(..4)),
+        ]);
+        let expected = r#"event = ws , ( "at" | "@" ) , hws , time , [ "!" ] , ( 2 * [ ( "a" | "p" ) ] , "m" ) , (* &str *) , ( 2 * digit | letter , { letter } ) ;
+time = digit , [ digit ] , ":" , 2 * digit ;
+hex = "0x" , 3 * [ ? any char ? ] ;
+"#;
+        assert_eq!(list, expected, "\n{list}");
+    }
+
+    #[test]
+    fn test_ebnf_quoting() {
+        let p = Seq::new()
+            .text("\"")
+            .chars_in(1.., &[',', '\'', '"'])
+            .text_alt(&["'", ", "]);
+        assert_eq!(
+            p.describe().to_ebnf("quoted"),
+            r#"quoted = '"' , ( "," | "'" | '"' ) , { ( "," | "'" | '"' ) } , ( "'" | ", " ) ;"#
+        );
+
+        use crate::combo::{StrParser, SP};
+        assert_eq!(
+            SP.ws().find("x").describe().to_ebnf("x"),
+            "x = ws , { ? any char ? } , \"x\" ;"
+        );
+    }
+}
@@ -6,6 +6,8 @@ use std::{
     str::ParseBoolError,
 };
 
+use crate::context;
+
 /// Indicates whether an error can be recovered from, and parsing can continue.
 /// Errors such as "config file not found" in parse functions are likely fatal and
 /// should be flagged non-recoverable
@@ -20,10 +22,53 @@ pub enum ParsingError {
         action: &'static str,
         args: &'static str,
     },
+    /// the text matched, but the value captured from it was rejected.
+    /// `offset` is the byte offset of the start of the value within the original input
+    Invalid {
+        action: &'static str,
+        offset: usize,
+        /// the `debug_context` path at the time, eg "hash > key_value"
+        context: String,
+        source: Option<Box<dyn Error>>,
+    },
+    /// the selected text could not be converted to `type_name`, with
+    /// `source` being the error from the conversion (eg `ParseIntError`), if kept.
+    /// `text` is None when converted by `?` from a std parse error
+    Conversion {
+        text: Option<String>,
+        type_name: &'static str,
+        context: String,
+        source: Option<Box<dyn Error>>,
+    },
 }
+
+impl ParsingError {
+    pub(crate) fn conversion<T>(text: &str, source: impl Into<Box<dyn Error>>) -> Self {
+        Self::Conversion {
+            text: Some(text.to_string()),
+            type_name: std::any::type_name::<T>(),
+            context: context::context_path(),
+            source: Some(source.into()),
+        }
+    }
+
+    // for a `FromStr` whose error type may not implement Error, so cannot be kept
+    pub(crate) fn conversion_of<T>(text: &str) -> Self {
+        Self::Conversion {
+            text: Some(text.to_string()),
+            type_name: std::any::type_name::<T>(),
+            context: context::context_path(),
+            source: None,
+        }
+    }
+}
+
 impl Recoverable for ParsingError {
     fn is_recoverable(&self) -> bool {
-        matches!(self, Self::NoMatch { .. })
+        matches!(
+            self,
+            Self::NoMatch { .. } | Self::Invalid { .. } | Self::Conversion { .. }
+        )
     }
 }
 
@@ -36,29 +81,36 @@ impl Default for ParsingError {
     }
 }
 
+// the text is unknown here, use parse_selection_keep_source to record it
 impl From<ParseIntError> for ParsingError {
-    fn from(_value: ParseIntError) -> Self {
-        ParsingError::NoMatch {
-            action: "parse int error",
-            args: "",
+    fn from(value: ParseIntError) -> Self {
+        ParsingError::Conversion {
+            text: None,
+            type_name: "integer",
+            context: context::context_path(),
+            source: Some(Box::new(value)),
         }
     }
 }
 
 impl From<ParseFloatError> for ParsingError {
-    fn from(_value: ParseFloatError) -> Self {
-        ParsingError::NoMatch {
-            action: "parse float error",
-            args: "",
+    fn from(value: ParseFloatError) -> Self {
+        ParsingError::Conversion {
+            text: None,
+            type_name: "float",
+            context: context::context_path(),
+            source: Some(Box::new(value)),
         }
     }
 }
 
 impl From<ParseBoolError> for ParsingError {
-    fn from(_value: ParseBoolError) -> Self {
-        ParsingError::NoMatch {
-            action: "parse bool error",
-            args: "",
+    fn from(value: ParseBoolError) -> Self {
+        ParsingError::Conversion {
+            text: None,
+            type_name: "bool",
+            context: context::context_path(),
+            source: Some(Box::new(value)),
         }
     }
 }
@@ -75,6 +127,28 @@ impl Clone for ParsingError {
         match self {
             Self::Fatal(_e) => Self::Fatal(None),
             Self::NoMatch { action, args } => Self::NoMatch { action, args },
+            Self::Invalid {
+                action,
+                offset,
+                context,
+                ..
+            } => Self::Invalid {
+                action,
+                offset: *offset,
+                context: context.clone(),
+                source: None,
+            },
+            Self::Conversion {
+                text,
+                type_name,
+                context,
+                ..
+            } => Self::Conversion {
+                text: text.clone(),
+                type_name,
+                context: context.clone(),
+                source: None,
+            },
         }
     }
 }
@@ -95,8 +169,57 @@ impl fmt::Display for ParsingError {
             Self::NoMatch { action, args } => {
                 write!(f, "FailedMatch: (action='{action}' args='{args}')")?
             }
+            Self::Invalid {
+                action,
+                offset,
+                context,
+                source,
+            } => {
+                write!(f, "Invalid: (action='{action}' offset={offset}")?;
+                write_context(f, context)?;
+                if let Some(e) = source {
+                    write!(f, " {e}")?;
+                }
+            }
+            Self::Conversion {
+                text,
+                type_name,
+                context,
+                source,
+            } => {
+                write!(f, "Conversion: (")?;
+                if let Some(text) = text {
+                    write!(f, "text='{text}' ")?;
+                }
+                write!(f, "type='{type_name}'")?;
+                write_context(f, context)?;
+                if let Some(e) = source {
+                    write!(f, " {e}")?;
+                }
+            }
         };
         Ok(())
     }
 }
-impl std::error::Error for ParsingError {}
+// closes the parenthesised details of Display
+fn write_context(f: &mut fmt::Formatter, context: &str) -> fmt::Result {
+    match context {
+        "" => write!(f, ")"),
+        _ => write!(f, " context='{context}')"),
+    }
+}
+
+impl std::error::Error for ParsingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Fatal(Some(e))
+            | Self::Invalid {
+                source: Some(e), ..
+            }
+            | Self::Conversion {
+                source: Some(e), ..
+            } => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,766 @@
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+    rc::Rc,
+    str::FromStr,
+};
+
+use crate::{
+    combo,
+    describe::{short_type_name, Arg, Describe, Node},
+    error::Recoverable,
+    prelude::{Cursor, Matchable, ParsingError, Selectable},
+};
+
+type Range = (Bound<i32>, Bound<i32>);
+
+fn range(r: impl RangeBounds<i32>) -> Range {
+    (r.start_bound().cloned(), r.end_bound().cloned())
+}
+
+/// The predicate of a recorded `chars_match`, shared by clones of the parser
+#[derive(Clone)]
+pub struct CharPred(Rc<dyn Fn(char) -> bool>);
+
+impl Debug for CharPred {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("CharPred")
+    }
+}
+
+/// A recorded (non-capturing) cursor method, replayed against each input
+#[derive(Debug, Clone)]
+pub enum Op {
+    DebugContext(&'static str),
+    Ws,
+    NonWs,
+    Hws,
+    Text(String),
+    Char(char),
+    Maybe(String),
+    TextAlt(Vec<String>),
+    EndOfStream,
+    EndOfLine,
+    Find(String),
+    ScanText(String),
+    ScanEol,
+    CharsIn(Range, Vec<char>),
+    CharsNotIn(Range, Vec<char>),
+    CharsAny(Range),
+    CharsMatch(Range, CharPred),
+    Digits(Range),
+    Word,
+    Alphabetics(Range),
+    Alphanumerics(Range),
+    SelectionStart,
+    SelectionEnd,
+    Select(Vec<Op>),
+    Repeat(Range, Vec<Op>),
+    Alt(Vec<Vec<Op>>),
+}
+
+fn apply_ops<'a, M: Selectable<'a>>(m: M, ops: &[Op]) -> M {
+    ops.iter().fold(m, apply_op)
+}
+
+fn apply_op<'a, M: Selectable<'a>>(m: M, op: &Op) -> M {
+    match op {
+        Op::DebugContext(name) => m.debug_context(name),
+        Op::Ws => m.ws(),
+        Op::NonWs => m.non_ws(),
+        Op::Hws => m.hws(),
+        Op::Text(word) => m.text(word),
+        Op::Char(ch) => m.char(*ch),
+        Op::Maybe(word) => m.maybe(word),
+        Op::TextAlt(words) => m.text_alt(&words.iter().map(String::as_str).collect::<Vec<_>>()),
+        Op::EndOfStream => m.end_of_stream(),
+        Op::EndOfLine => m.end_of_line(),
+        Op::Find(needle) => m.find(needle),
+        Op::ScanText(needle) => m.scan_text(needle),
+        Op::ScanEol => m.scan_eol(),
+        Op::CharsIn(r, chars) => m.chars_in(*r, chars),
+        Op::CharsNotIn(r, chars) => m.chars_not_in(*r, chars),
+        Op::CharsAny(r) => m.chars_any(*r),
+        Op::CharsMatch(r, pred) => m.chars_match(*r, |ch| (pred.0)(ch)),
+        Op::Digits(r) => m.digits(*r),
+        Op::Word => m.word(),
+        Op::Alphabetics(r) => m.alphabetics(*r),
+        Op::Alphanumerics(r) => m.alphanumerics(*r),
+        Op::SelectionStart => m.selection_start(),
+        Op::SelectionEnd => m.selection_end(),
+        Op::Select(ops) => m.select(|m| apply_ops(m, ops)),
+        Op::Repeat(r, ops) => sub_parse(m, |s| repeat(s, r, ops)),
+        Op::Alt(alts) => sub_parse(m, |s| alt(s, alts)),
+    }
+}
+
+/// runs a nested sequence as a stir-style parser on the remaining input,
+/// as cursors holding captures cannot be cloned to backtrack
+fn sub_parse<'a, M: Matchable<'a>>(
+    m: M,
+    f: impl FnOnce(&'a str) -> Result<&'a str, ParsingError>,
+) -> M {
+    let Ok(s) = m.str() else {
+        return m;
+    };
+    match f(s) {
+        Ok(rest) => m.set_str(rest),
+        Err(e) => m.set_error(e),
+    }
+}
+
+fn run_ops<'a>(s: &'a str, ops: &[Op]) -> Result<&'a str, ParsingError> {
+    apply_ops(Cursor::from(s), ops).validate()
+}
+
+// the first alternative to match, stopping at any fatal error (eg a LimitError)
+fn alt<'a>(s: &'a str, alts: &[Vec<Op>]) -> Result<&'a str, ParsingError> {
+    for ops in alts {
+        match run_ops(s, ops) {
+            Ok(rest) => return Ok(rest),
+            Err(e) if !e.is_recoverable() => return Err(e),
+            Err(_) => {}
+        }
+    }
+    Err(ParsingError::NoMatch {
+        action: "alt",
+        args: "",
+    })
+}
+
+fn repeat<'a>(mut s: &'a str, r: &Range, ops: &[Op]) -> Result<&'a str, ParsingError> {
+    let min = match r.start_bound() {
+        Bound::Included(&i) => i,
+        Bound::Excluded(&i) => i + 1,
+        Bound::Unbounded => 0,
+    };
+    let max = match r.end_bound() {
+        Bound::Included(&i) => i,
+        Bound::Excluded(&i) => i - 1,
+        Bound::Unbounded => i32::MAX,
+    };
+    let mut count = 0;
+    while count < max {
+        match run_ops(s, ops) {
+            // stop if no progress, rather than looping forever
+            Ok(rest) if rest.len() < s.len() => s = rest,
+            Ok(_) => break,
+            Err(e) if !e.is_recoverable() => return Err(e),
+            Err(_) => break,
+        }
+        count += 1;
+    }
+    if count < min {
+        return Err(ParsingError::NoMatch {
+            action: "repeat",
+            args: "",
+        });
+    }
+    Ok(s)
+}
+
+/// Builds a reusable parser from the same methods as the eager `Matchable` and
+/// `Selectable` cursor API. Nothing is parsed until the parser is run (see [`RunLazy`]),
+/// so a parser can be built once and run against many inputs. A `Seq` is also a
+/// `combo::Parser`, so can be chained with the combo parsers.
+///
+/// ```
+/// use daisychain::prelude::*;
+/// use daisychain::prelude::lazy::{Lazy, RunLazy, Seq};
+///
+/// let time = Seq::new()
+///     .digits(2..=2)
+///     .parse_selection::<u32>()
+///     .text(":")
+///     .digits(2..=2)
+///     .parse_selection::<u32>();
+///
+/// assert_eq!(time.run("12:30pm").unwrap(), ("pm", 12, 30));
+/// assert_eq!(time.run("09:15").unwrap(), ("", 9, 15));
+/// assert_eq!(time.run("9:15").is_err(), true);
+/// ```
+pub trait Lazy: Sized {
+    #[doc(hidden)]
+    fn push(self, op: Op) -> Self;
+
+    fn debug_context(self, span_name: &'static str) -> Self {
+        self.push(Op::DebugContext(span_name))
+    }
+
+    fn ws(self) -> Self {
+        self.push(Op::Ws)
+    }
+
+    fn non_ws(self) -> Self {
+        self.push(Op::NonWs)
+    }
+
+    fn hws(self) -> Self {
+        self.push(Op::Hws)
+    }
+
+    fn text(self, word: &str) -> Self {
+        self.push(Op::Text(word.to_string()))
+    }
+
+    fn char(self, ch: char) -> Self {
+        self.push(Op::Char(ch))
+    }
+
+    fn maybe(self, word: &str) -> Self {
+        self.push(Op::Maybe(word.to_string()))
+    }
+
+    fn text_alt(self, words: &[&str]) -> Self {
+        self.push(Op::TextAlt(words.iter().map(|w| w.to_string()).collect()))
+    }
+
+    fn end_of_stream(self) -> Self {
+        self.push(Op::EndOfStream)
+    }
+
+    fn end_of_line(self) -> Self {
+        self.push(Op::EndOfLine)
+    }
+
+    fn find(self, needle: &str) -> Self {
+        self.push(Op::Find(needle.to_string()))
+    }
+
+    fn scan_text(self, needle: &str) -> Self {
+        self.push(Op::ScanText(needle.to_string()))
+    }
+
+    fn scan_eol(self) -> Self {
+        self.push(Op::ScanEol)
+    }
+
+    fn chars_in<R: RangeBounds<i32>>(self, r: R, chars: &[char]) -> Self {
+        self.push(Op::CharsIn(range(r), chars.to_vec()))
+    }
+
+    fn chars_not_in<R: RangeBounds<i32>>(self, r: R, chars: &[char]) -> Self {
+        self.push(Op::CharsNotIn(range(r), chars.to_vec()))
+    }
+
+    fn chars_any<R: RangeBounds<i32>>(self, r: R) -> Self {
+        self.push(Op::CharsAny(range(r)))
+    }
+
+    /// as the eager `chars_match`, but the predicate is kept to be called on each run,
+    /// so is an `Fn` rather than an `FnMut`
+    fn chars_match<R, F>(self, r: R, pred: F) -> Self
+    where
+        R: RangeBounds<i32>,
+        F: Fn(char) -> bool + 'static,
+    {
+        self.push(Op::CharsMatch(range(r), CharPred(Rc::new(pred))))
+    }
+
+    fn digits<R: RangeBounds<i32>>(self, r: R) -> Self {
+        self.push(Op::Digits(range(r)))
+    }
+
+    fn word(self) -> Self {
+        self.push(Op::Word)
+    }
+
+    fn alphabetics<R: RangeBounds<i32>>(self, r: R) -> Self {
+        self.push(Op::Alphabetics(range(r)))
+    }
+
+    fn alphanumerics<R: RangeBounds<i32>>(self, r: R) -> Self {
+        self.push(Op::Alphanumerics(range(r)))
+    }
+
+    fn selection_start(self) -> Self {
+        self.push(Op::SelectionStart)
+    }
+
+    fn selection_end(self) -> Self {
+        self.push(Op::SelectionEnd)
+    }
+
+    fn select(self, f: impl FnOnce(Seq) -> Seq) -> Self {
+        self.push(Op::Select(f(Seq::new()).ops))
+    }
+
+    /// matches the sequence repeatedly, failing if it matches fewer than `range.start` times
+    /// (see [`Seq`] for how this differs from the eager `repeat`)
+    fn repeat<R: RangeBounds<i32>>(self, r: R, f: impl FnOnce(Seq) -> Seq) -> Self {
+        self.push(Op::Repeat(range(r), f(Seq::new()).ops))
+    }
+
+    /// matches the first alternative that succeeds. Only the position reached carries
+    /// over, so wrap it in `select` to select what was matched (see [`Seq`])
+    fn alt(self, alternatives: impl IntoIterator<Item = Seq>) -> Self {
+        self.push(Op::Alt(
+            alternatives.into_iter().map(|seq| seq.ops).collect(),
+        ))
+    }
+
+    fn parse_selection<T>(self) -> Then<Self, Selection<T>>
+    where
+        T: FromStr + Debug,
+    {
+        Then::new(self, Selection(PhantomData))
+    }
+
+    fn parse_selection_as_str(self) -> Then<Self, SelectionStr> {
+        Then::new(self, SelectionStr)
+    }
+
+    /// captures the result of a stir-style parser
+    fn parse_with<F, T>(self, parser: F) -> Then<Self, With<F, T>>
+    where
+        F: for<'s> Fn(&'s str) -> Result<(&'s str, T), ParsingError>,
+    {
+        Then::new(self, With(parser, PhantomData))
+    }
+}
+
+/// Runs a lazily built parser
+pub trait RunLazy<'a> {
+    type Output: Selectable<'a>;
+
+    /// runs the parser from the cursor's position, returning the cursor chain
+    /// so that eager cursor methods can follow
+    fn run_cursor(&self, c: Cursor<'a>) -> Self::Output;
+
+    fn run(&self, s: &'a str) -> Result<<Self::Output as Matchable<'a>>::DeTuple, ParsingError> {
+        self.run_cursor(Cursor::from(s)).validate()
+    }
+
+    /// stir-style parse, for parsers capturing a single value.
+    /// Used as `cursor.parse_with(|s| lazy.parse(s))` from the eager API
+    fn parse<T>(&self, s: &'a str) -> Result<(&'a str, T), ParsingError>
+    where
+        Self::Output: Matchable<'a, DeTuple = (&'a str, T)>,
+    {
+        self.run(s)
+    }
+}
+
+/// The starting point of a lazily built parser, see [`Lazy`].
+///
+/// Running a parser gives the same result as calling the recorded methods on a `Cursor`,
+/// except for:
+/// - `repeat`, which fails if the sequence matched fewer than `range.start` times, where
+///   the eager `repeat` accepts any count up to the maximum. It also stops at an iteration
+///   matching nothing, and passes on fatal errors (eg from [`Limits`](crate::prelude::Limits))
+///   where the eager one stops at any error.
+/// - `repeat` and `alt` (which has no eager method) run their sequences as nested parses
+///   of the remaining input, carrying over only the position reached. Selections and
+///   `debug_context` labels made within them are discarded, so select around them instead,
+///   as in `select(|s| s.alt(..))`.
+/// - `chars_match`, which takes an `Fn` rather than an `FnMut`, as it is called on each run.
+#[derive(Debug, Clone, Default)]
+pub struct Seq {
+    ops: Vec<Op>,
+}
+
+impl Seq {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Lazy for Seq {
+    fn push(mut self, op: Op) -> Self {
+        self.ops.push(op);
+        self
+    }
+}
+
+impl<'a> RunLazy<'a> for Seq {
+    type Output = Cursor<'a>;
+
+    fn run_cursor(&self, c: Cursor<'a>) -> Self::Output {
+        apply_ops(c, &self.ops)
+    }
+}
+
+/// A lazily built parser `P`, followed by the capture of a value, and then further matching
+#[derive(Debug, Clone)]
+pub struct Then<P, K> {
+    prev: P,
+    capture: K,
+    ops: Vec<Op>,
+}
+
+impl<P, K> Then<P, K> {
+    fn new(prev: P, capture: K) -> Self {
+        Self {
+            prev,
+            capture,
+            ops: vec![],
+        }
+    }
+}
+
+impl<P, K> Lazy for Then<P, K> {
+    fn push(mut self, op: Op) -> Self {
+        self.ops.push(op);
+        self
+    }
+}
+
+impl<'a, P, K> RunLazy<'a> for Then<P, K>
+where
+    P: RunLazy<'a>,
+    K: Capture<'a, P::Output>,
+{
+    type Output = K::Output;
+
+    fn run_cursor(&self, c: Cursor<'a>) -> Self::Output {
+        let m = self.capture.capture(self.prev.run_cursor(c));
+        apply_ops(m, &self.ops)
+    }
+}
+
+/// How a value is captured by [`Then`]
+pub trait Capture<'a, M: Selectable<'a>> {
+    type Output: Selectable<'a>;
+    fn capture(&self, m: M) -> Self::Output;
+}
+
+#[derive(Debug, Clone)]
+pub struct Selection<T>(PhantomData<fn() -> T>);
+
+impl<'a, M, T> Capture<'a, M> for Selection<T>
+where
+    M: Selectable<'a>,
+    T: FromStr + Debug,
+    (M, Option<T>): Selectable<'a>,
+{
+    type Output = (M, Option<T>);
+
+    fn capture(&self, m: M) -> Self::Output {
+        m.parse_selection()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SelectionStr;
+
+impl<'a, M> Capture<'a, M> for SelectionStr
+where
+    M: Selectable<'a>,
+    (M, Option<&'a str>): Selectable<'a>,
+{
+    type Output = (M, Option<&'a str>);
+
+    fn capture(&self, m: M) -> Self::Output {
+        m.parse_selection_as_str()
+    }
+}
+
+#[derive(Clone)]
+pub struct With<F, T>(F, PhantomData<fn() -> T>);
+
+impl<'a, M, F, T> Capture<'a, M> for With<F, T>
+where
+    M: Selectable<'a> + Matchable<'a, Cursor = Cursor<'a>>,
+    F: for<'s> Fn(&'s str) -> Result<(&'s str, T), ParsingError>,
+    (M, Option<T>): Selectable<'a>,
+{
+    type Output = (M, Option<T>);
+
+    fn capture(&self, m: M) -> Self::Output {
+        m.parse_with(|s: &'a str| (self.0)(s))
+    }
+}
+
+// so that lazily built parsers can be combined with `chain_parser`
+impl<'a> combo::Parser<'a> for Seq {
+    type Input = &'a str;
+    type Output = &'a str;
+    type Error = ParsingError;
+
+    fn validate(&mut self, inp: Self::Input) -> Result<Self::Output, Self::Error> {
+        self.run(inp)
+    }
+}
+
+impl<'a, P, K> combo::Parser<'a> for Then<P, K>
+where
+    Self: RunLazy<'a>,
+    <<Self as RunLazy<'a>>::Output as Matchable<'a>>::DeTuple: Debug,
+{
+    type Input = &'a str;
+    type Output = <<Self as RunLazy<'a>>::Output as Matchable<'a>>::DeTuple;
+    type Error = ParsingError;
+
+    fn validate(&mut self, inp: Self::Input) -> Result<Self::Output, Self::Error> {
+        self.run(inp)
+    }
+}
+
+fn range_arg(&(start, end): &Range) -> Arg {
+    Arg::Range(start, end)
+}
+
+impl Describe for Op {
+    fn describe(&self) -> Node {
+        let text = |s: &str| Arg::Text(s.to_string());
+        match self {
+            Op::DebugContext(name) => Node::new("debug_context").arg(Arg::Name(name.to_string())),
+            Op::Ws => Node::new("ws"),
+            Op::NonWs => Node::new("non_ws"),
+            Op::Hws => Node::new("hws"),
+            Op::Text(word) => Node::new("text").arg(text(word)),
+            Op::Char(ch) => Node::new("char").arg(text(&ch.to_string())),
+            Op::Maybe(word) => Node::new("maybe").arg(text(word)),
+            Op::TextAlt(words) => Node {
+                args: words.iter().map(|w| text(w)).collect(),
+                ..Node::new("text_alt")
+            },
+            Op::EndOfStream => Node::new("end_of_stream"),
+            Op::EndOfLine => Node::new("end_of_line"),
+            Op::Find(needle) => Node::new("find").arg(text(needle)),
+            Op::ScanText(needle) => Node::new("scan_text").arg(text(needle)),
+            Op::ScanEol => Node::new("scan_eol"),
+            Op::CharsIn(r, chars) => Node::new("chars_in")
+                .arg(range_arg(r))
+                .arg(Arg::Chars(chars.clone())),
+            Op::CharsNotIn(r, chars) => Node::new("chars_not_in")
+                .arg(range_arg(r))
+                .arg(Arg::Chars(chars.clone())),
+            Op::CharsAny(r) => Node::new("chars_any").arg(range_arg(r)),
+            Op::CharsMatch(r, _) => Node::new("chars_match").arg(range_arg(r)),
+            Op::Digits(r) => Node::new("digits").arg(range_arg(r)),
+            Op::Word => Node::new("word"),
+            Op::Alphabetics(r) => Node::new("alphabetics").arg(range_arg(r)),
+            Op::Alphanumerics(r) => Node::new("alphanumerics").arg(range_arg(r)),
+            Op::SelectionStart => Node::new("selection_start"),
+            Op::SelectionEnd => Node::new("selection_end"),
+            Op::Select(ops) => Node {
+                children: ops.iter().map(Op::describe).collect(),
+                ..Node::new("select")
+            },
+            Op::Repeat(r, ops) => Node {
+                children: ops.iter().map(Op::describe).collect(),
+                ..Node::new("repeat").arg(range_arg(r))
+            },
+            Op::Alt(alts) => Node {
+                children: alts
+                    .iter()
+                    .map(|ops| Node::seq(ops.iter().map(Op::describe)))
+                    .collect(),
+                ..Node::new("alt")
+            },
+        }
+    }
+}
+
+impl Describe for Seq {
+    fn describe(&self) -> Node {
+        Node::seq(self.ops.iter().map(Op::describe))
+    }
+}
+
+impl<P: Describe, K: Describe> Describe for Then<P, K> {
+    fn describe(&self) -> Node {
+        let steps = [self.prev.describe(), self.capture.describe()];
+        Node::seq(steps.into_iter().chain(self.ops.iter().map(Op::describe)))
+    }
+}
+
+impl<T> Describe for Selection<T> {
+    fn describe(&self) -> Node {
+        Node::new("parse_selection").arg(Arg::Name(short_type_name(std::any::type_name::<T>())))
+    }
+}
+
+impl Describe for SelectionStr {
+    fn describe(&self) -> Node {
+        Node::new("parse_selection_as_str").arg(Arg::Name("&str".to_string()))
+    }
+}
+
+impl<F, T> Describe for With<F, T> {
+    fn describe(&self) -> Node {
+        Node::new("parse_with").arg(Arg::Name(short_type_name(std::any::type_name::<F>())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combo::Parser;
+    use crate::prelude::Limits;
+    use test_log::test;
+
+    #[derive(Debug, PartialEq)]
+    struct Time(u32, u32);
+
+    #[test]
+    fn test_lazy_matches_eager() {
+        let p = Seq::new()
+            .ws()
+            .text_alt(&["Mon", "Tue"])
+            .hws()
+            .select(|s| s.digits(2..=2).char(':').digits(2..=2))
+            .parse_selection_as_str()
+            .maybe("!")
+            .select(|s| s.chars_in(1.., &['a', 'p']))
+            .parse_selection_as_str()
+            .char('m')
+            .end_of_stream();
+        let inputs = [
+            "  Mon 12:30pm",
+            "Tue 07:05am",
+            "Wed 12:30pm",
+            "Mon 12:30!pm",
+        ];
+        for s in inputs {
+            let eager = Cursor::from(s)
+                .ws()
+                .text_alt(&["Mon", "Tue"])
+                .hws()
+                .select(|c| c.digits(2..=2).char(':').digits(2..=2))
+                .parse_selection_as_str()
+                .maybe("!")
+                .select(|c| c.chars_in(1.., &['a', 'p']))
+                .parse_selection_as_str()
+                .char('m')
+                .end_of_stream()
+                .validate();
+            assert_eq!(p.run(s).ok(), eager.ok(), "{s}");
+        }
+        assert_eq!(p.run("Tue 07:05am").unwrap(), ("", "07:05", "a"));
+    }
+
+    #[test]
+    fn test_alt_and_repeat() {
+        let number = Seq::new()
+            .alt([
+                Seq::new()
+                    .text("0x")
+                    .chars_match(1.., |c| c.is_ascii_hexdigit()),
+                Seq::new().text("0b").chars_in(1.., &['0', '1']),
+                Seq::new().digits(1..),
+            ])
+            .parse_selection_as_str();
+        assert_eq!(number.run("0x1Fg").unwrap(), ("g", "0x1F"));
+        assert_eq!(number.run("0b102").unwrap(), ("2", "0b10"));
+        assert_eq!(number.run("42").unwrap(), ("", "42"));
+        assert_eq!(number.run("x").is_err(), true);
+
+        let list = Seq::new()
+            .text("[")
+            .repeat(2..=3, |s| s.digits(1..).maybe(","))
+            .text("]");
+        assert_eq!(list.run("[1,2]").is_ok(), true);
+        assert_eq!(list.run("[1,2,3]").is_ok(), true);
+        assert_eq!(list.run("[1]").is_err(), true);
+        assert_eq!(list.run("[1,2,3,4]").is_err(), true);
+
+        // a fatal error in an alternative is not masked by trying the next one
+        let res = Limits::new().max_steps(2).parse("0b101", |s| {
+            let e = number.run(s).unwrap_err();
+            assert_eq!(e.is_recoverable(), false, "{e}");
+            Err::<(&str, ()), _>(e)
+        });
+        assert_eq!(res.is_err(), true);
+    }
+
+    #[test]
+    fn test_repeat_alt_match_eager() {
+        // within the repeat's minimum, lazy and eager agree
+        let list = Seq::new()
+            .text("[")
+            .repeat(0..=3, |s| s.digits(1..).maybe(","))
+            .text("]")
+            .end_of_stream();
+        for s in ["[]", "[1]", "[1,2,3]", "[1,2,3,4]", "[x]", "[1,"] {
+            let eager = Cursor::from(s)
+                .text("[")
+                .repeat(0..=3, |c| c.digits(1..).maybe(","))
+                .text("]")
+                .end_of_stream()
+                .validate();
+            assert_eq!(list.run(s).ok(), eager.ok(), "{s}");
+        }
+
+        // but only the lazy repeat enforces the minimum
+        let lazy = Seq::new().repeat(2.., |s| s.digits(1..).maybe(","));
+        assert_eq!(lazy.run("1").is_err(), true);
+        let eager = Cursor::from("1")
+            .repeat(2.., |c| c.digits(1..).maybe(","))
+            .validate();
+        assert_eq!(eager.unwrap(), "");
+
+        // an alt selected as a whole, against eager alternatives tried in turn
+        let radix = 16;
+        let number = Seq::new()
+            .select(|s| {
+                s.alt([
+                    Seq::new()
+                        .text("0x")
+                        .chars_match(1.., move |c| c.is_digit(radix)),
+                    Seq::new().digits(1..),
+                ])
+            })
+            .parse_selection_as_str();
+        for s in ["0x1fz", "0x", "12a", "z"] {
+            let hex =
+                Cursor::from(s).select(|c| c.text("0x").chars_match(1.., |c| c.is_digit(radix)));
+            let eager = match hex.str() {
+                Ok(_) => hex,
+                Err(_) => Cursor::from(s).select(|c| c.digits(1..)),
+            };
+            let eager = eager.parse_selection_as_str().validate();
+            assert_eq!(number.run(s).ok(), eager.ok(), "{s}");
+        }
+        assert_eq!(number.run("0x1fz").unwrap(), ("z", "0x1f"));
+    }
+
+    #[test]
+    fn test_lazy_with_cursor() {
+        fn time(s: &str) -> Result<(&str, Time), ParsingError> {
+            let (c, h, m) = Cursor::from(s)
+                .digits(2..=2)
+                .parse_selection()
+                .text(":")
+                .digits(2..=2)
+                .parse_selection()
+                .validate()?;
+            Ok((c, Time(h, m)))
+        }
+        // eager stir-style parsers within lazy ones
+        let event = Seq::new()
+            .alphabetics(1..)
+            .parse_selection::<String>()
+            .text(" at ")
+            .parse_with(time);
+        let (c, name, t) = event.run("lunch at 12:30.").unwrap();
+        assert_eq!((c, name.as_str(), t), (".", "lunch", Time(12, 30)));
+
+        // and lazy within eager
+        let hours = Seq::new().digits(1..).parse_selection::<u32>().text("h");
+        let (c, h1, h2) = Cursor::from("3h + 4h")
+            .parse_with(|s| hours.parse(s))
+            .text(" + ")
+            .parse_with(|s| hours.parse(s))
+            .validate()
+            .unwrap();
+        assert_eq!((c, h1, h2), ("", 3, 4));
+
+        // run from an existing cursor, then continue eagerly
+        let cur = Cursor::from("at 10:15 today").text("at ");
+        let (c, t) = Seq::new()
+            .parse_with(time)
+            .run_cursor(cur)
+            .ws()
+            .text("today")
+            .validate()
+            .unwrap();
+        assert_eq!((c, t), ("", Time(10, 15)));
+
+        // and via the combo::Parser trait
+        let mut p = Seq::new().text("x").parse_selection_as_str();
+        assert_eq!(Parser::validate(&mut p, "xyz").unwrap(), ("yz", "x"));
+    }
+}
@@ -11,17 +11,24 @@
 
 
 
-use std::cell::Cell;
-
-
-mod contrib;
+pub mod contrib;
 mod logging;
 mod parser;
 mod cursor;
 mod error;
 mod text_parser;
+mod tracer;
 mod util;
 mod combo;
+mod context;
+mod describe;
+mod lazy;
+mod limits;
+mod pratt;
+mod profiler;
+mod report;
+mod span;
+mod rule;
 
 pub mod prelude;
 
@@ -30,5 +37,23 @@ pub mod cookbook;
 
 pub(crate) const LOG_TARGET: &str = "dc"; // env!("CARGO_PKG_NAME");
 
-thread_local!(pub(crate) static LABEL: Cell<&'static str> = Cell::new(""));
-
+pub mod expr {
+    pub use crate::pratt::{Assoc, ExprParser, Fold};
+}
+
+pub mod grammar {
+    pub use crate::rule::{Grammar, GrammarError, MemoStats, RuleStats};
+}
+
+pub mod introspect {
+    pub use crate::describe::{ebnf, Arg, Describe, Node};
+}
+
+pub mod trace {
+    #[cfg(feature = "tracing")]
+    pub use crate::tracer::TracingTracer;
+    pub use crate::context::{context_label, context_path, ContextPath};
+    pub use crate::profiler::{Profile, Profiler, SectionKind, SectionStats};
+    pub use crate::report::html_report;
+    pub use crate::tracer::{with_tracer, Event, EventKind, LogTracer, Record, Recorder, Tracer};
+}
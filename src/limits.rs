@@ -0,0 +1,477 @@
+use std::{cell::Cell, error::Error, fmt};
+
+#[cfg(feature = "trace")]
+use log::trace;
+
+#[cfg(feature = "trace")]
+use crate::LOG_TARGET;
+use crate::{context, prelude::ParsingError, span::Origin};
+
+/// Fatal error raised when a parse exceeds one of its [`Limits`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitError {
+    Depth { depth: usize, offset: usize },
+    Steps { steps: u64, offset: usize },
+    InputLength { len: usize, max: usize },
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Depth { depth, offset } => {
+                write!(
+                    f,
+                    "maximum nesting depth {depth} exceeded at offset {offset}"
+                )
+            }
+            Self::Steps { steps, offset } => {
+                write!(f, "step budget of {steps} exhausted at offset {offset}")
+            }
+            Self::InputLength { len, max } => {
+                write!(f, "input length {len} exceeds maximum of {max}")
+            }
+        }
+    }
+}
+
+impl Error for LimitError {}
+
+impl From<LimitError> for ParsingError {
+    fn from(e: LimitError) -> Self {
+        ParsingError::Fatal(Some(Box::new(e)))
+    }
+}
+
+#[derive(Clone, Copy)]
+struct State {
+    depth: usize,
+    max_depth: usize,
+    steps: u64,
+    max_steps: u64,
+    max_input_len: usize,
+    origin: Origin,
+    exceeded: Option<LimitError>,
+    // within `Limits::parse`
+    limited: bool,
+}
+
+impl State {
+    const UNLIMITED: State = State {
+        depth: 0,
+        max_depth: usize::MAX,
+        steps: 0,
+        max_steps: u64::MAX,
+        max_input_len: usize::MAX,
+        origin: Origin::NONE,
+        exceeded: None,
+        limited: false,
+    };
+
+    fn offset(&self, s: &str) -> usize {
+        self.origin.offset(s)
+    }
+}
+
+// stir-style parsers create a new Cursor for each nested parse, so the
+// limits, counters and origin of the input live alongside the parse rather than in the Cursor
+thread_local!(static STATE: Cell<State> = const { Cell::new(State::UNLIMITED) });
+
+/// Limits placed on a parse, to guard against malicious or pathological input.
+///
+/// Nesting depth is the number of nested `parse_with`, `parse_struct_vec` and
+/// [`Grammar`](crate::grammar::Grammar) rule invocations currently in progress.
+/// Steps are primitive matches such as `text`, `ws` or `digits`, whether they succeed or not,
+/// so a step budget bounds the work done by backtracking through `repeat` or alternatives.
+/// The input length limit applies to the text given to `Cursor::from`.
+///
+/// Once a limit is exceeded, every nested parser fails, and the parse as a whole
+/// returns a non-recoverable error, even if intermediate parsers discarded it.
+///
+/// ```
+/// use daisychain::prelude::*;
+///
+/// fn brackets(s: &str) -> Result<(&str, usize), ParsingError> {
+///     if let Ok((c, depth)) = Cursor::from(s).text("[").parse_with(brackets).text("]").validate() {
+///         return Ok((c, depth + 1));
+///     }
+///     Ok((s, 0))
+/// }
+///
+/// let limits = Limits::new().max_depth(10);
+/// assert_eq!(limits.parse("[[[]]]", brackets).unwrap().1, 3);
+///
+/// let nested = "[".repeat(100_000) + &"]".repeat(100_000);
+/// let err = limits.parse(&nested, brackets).unwrap_err();
+/// assert_eq!(err.to_string(), "Fatal:maximum nesting depth 11 exceeded at offset 11");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    max_depth: Option<usize>,
+    max_steps: Option<u64>,
+    max_input_len: Option<usize>,
+}
+
+impl Limits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn max_steps(mut self, max_steps: u64) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// maximum length in bytes
+    pub fn max_input_len(mut self, max_input_len: usize) -> Self {
+        self.max_input_len = Some(max_input_len);
+        self
+    }
+
+    /// run a stir-style parser with these limits in force (on this thread).
+    ///
+    /// Within another limited parse, the tighter of each limit applies, steps are
+    /// charged to the outer parse too, and a limit exceeded fails the outer parse.
+    pub fn parse<'a, P, T>(&self, s: &'a str, parser: P) -> Result<(&'a str, T), ParsingError>
+    where
+        P: FnOnce(&'a str) -> Result<(&'a str, T), ParsingError>,
+    {
+        let prior = STATE.with(|st| st.get());
+        let max_depth = self.max_depth.unwrap_or(usize::MAX);
+        let max_steps = self.max_steps.unwrap_or(u64::MAX);
+        let max_input_len = self.max_input_len.unwrap_or(usize::MAX);
+        let state = State {
+            depth: 0,
+            max_depth: max_depth.min(prior.max_depth.saturating_sub(prior.depth)),
+            steps: 0,
+            max_steps: max_steps.min(prior.max_steps.saturating_sub(prior.steps)),
+            max_input_len: max_input_len.min(prior.max_input_len),
+            origin: Origin::of(s),
+            exceeded: prior.exceeded,
+            limited: true,
+        };
+
+        STATE.with(|st| st.set(state));
+        let context = context::enter();
+        let res = check_input_len(s).and_then(|_| parser(s));
+        drop(context);
+        let state = STATE.with(|st| st.get());
+        STATE.with(|st| match prior.limited {
+            true => st.set(State {
+                steps: prior.steps.saturating_add(state.steps),
+                exceeded: state.exceeded,
+                ..prior
+            }),
+            false => st.set(prior),
+        });
+        match state.exceeded {
+            Some(e) => Err(e.into()),
+            None => res,
+        }
+    }
+}
+
+/// decrements the nesting depth (and restores the origin and context) when dropped
+pub(crate) struct DepthGuard {
+    prior_origin: Origin,
+    _context: context::Scope,
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        STATE.with(|st| {
+            let mut state = st.get();
+            state.depth = state.depth.saturating_sub(1);
+            state.origin = self.prior_origin;
+            st.set(state);
+        });
+    }
+}
+
+/// called on entry to a nested parser, with the input at that point
+pub(crate) fn enter(s: &str) -> Result<DepthGuard, ParsingError> {
+    enter_from(None, s)
+}
+
+/// as `enter`, with cursors created by the nested parser sharing the given origin
+pub(crate) fn enter_from(origin: Option<Origin>, s: &str) -> Result<DepthGuard, ParsingError> {
+    STATE.with(|st| {
+        let mut state = st.get();
+        if let Some(e) = state.exceeded {
+            return Err(e.into());
+        }
+        if state.depth >= state.max_depth {
+            let e = LimitError::Depth {
+                depth: state.depth + 1,
+                offset: state.offset(s),
+            };
+            #[cfg(feature = "trace")]
+            trace!(target: LOG_TARGET, "{e}");
+            state.exceeded = Some(e);
+            st.set(state);
+            return Err(e.into());
+        }
+        let guard = DepthGuard {
+            prior_origin: state.origin,
+            _context: context::enter(),
+        };
+        state.depth += 1;
+        state.origin = origin.unwrap_or(state.origin);
+        st.set(state);
+        Ok(guard)
+    })
+}
+
+/// called by each primitive match, with the input at that point
+#[inline]
+pub(crate) fn step(s: &str) -> Result<(), ParsingError> {
+    STATE.with(|st| {
+        let mut state = st.get();
+        if let Some(e) = state.exceeded {
+            return Err(e.into());
+        }
+        state.steps += 1;
+        if state.steps > state.max_steps {
+            let e = LimitError::Steps {
+                steps: state.max_steps,
+                offset: state.offset(s),
+            };
+            #[cfg(feature = "trace")]
+            trace!(target: LOG_TARGET, "{e}");
+            state.exceeded = Some(e);
+            st.set(state);
+            return Err(e.into());
+        }
+        st.set(state);
+        Ok(())
+    })
+}
+
+/// the origin for a cursor on `s`, which is `s` itself unless within a nested parse
+pub(crate) fn origin_of(s: &str) -> Origin {
+    STATE.with(|st| {
+        let origin = st.get().origin;
+        if origin.contains(s) {
+            origin
+        } else {
+            Origin::of(s)
+        }
+    })
+}
+
+pub(crate) fn check_input_len(s: &str) -> Result<(), ParsingError> {
+    STATE.with(|st| {
+        let mut state = st.get();
+        if s.len() > state.max_input_len {
+            let e = LimitError::InputLength {
+                len: s.len(),
+                max: state.max_input_len,
+            };
+            #[cfg(feature = "trace")]
+            trace!(target: LOG_TARGET, "{e}");
+            state.exceeded = Some(e);
+            st.set(state);
+            return Err(e.into());
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Recoverable;
+    use crate::grammar::Grammar;
+    use crate::prelude::*;
+    use test_log::test;
+
+    // swallows errors in the style of alternation with "if let Ok"
+    fn list(s: &str) -> Result<(&str, usize), ParsingError> {
+        if let Ok((c, v)) = Cursor::from(s)
+            .text("[")
+            .parse_struct_vec(|s| Cursor::from(s).parse_with(list).maybe(",").validate())
+            .text("]")
+            .validate()
+        {
+            return Ok((c, 1 + v.into_iter().max().unwrap_or_default()));
+        }
+        Err(ParsingError::default())
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let limits = Limits::new().max_depth(8);
+        assert_eq!(limits.parse("[[],[[]]]", list).unwrap(), ("", 3));
+        assert_eq!(limits.parse("[[[[]]]]", list).unwrap().1, 4);
+
+        let deep = "[".repeat(5) + &"]".repeat(5);
+        let e = limits.parse(&deep, list).unwrap_err();
+        assert_eq!(e.is_recoverable(), false);
+        assert_eq!(
+            e.to_string(),
+            "Fatal:maximum nesting depth 9 exceeded at offset 5"
+        );
+
+        // state is restored after a limited parse
+        assert_eq!(list(&deep).unwrap().1, 5);
+        assert_eq!(Limits::new().parse(&deep, list).unwrap().1, 5);
+    }
+
+    #[test]
+    fn test_max_depth_grammar() {
+        let g = Grammar::new().rule("nest", |g, s| {
+            let (c, depth) = Cursor::from(s)
+                .text("(")
+                .parse_with(|s| g.parse("nest", s))
+                .text(")")
+                .validate()
+                .unwrap_or((s, 0));
+            Ok((c, depth + 1))
+        });
+        // each level is both a rule and a parse_with
+        let limits = Limits::new().max_depth(5);
+        assert_eq!(limits.parse("(())", |s| g.parse("nest", s)).unwrap().1, 3);
+        assert_eq!(
+            limits
+                .parse("((()))", |s| g.parse("nest", s))
+                .unwrap_err()
+                .to_string(),
+            "Fatal:maximum nesting depth 6 exceeded at offset 3"
+        );
+    }
+
+    #[test]
+    fn test_max_steps() {
+        // exponential backtracking: ('a' | 'aa')* 'b' against input without a 'b'
+        fn a_or_aa(s: &str) -> Result<(&str, usize), ParsingError> {
+            if let Ok((c, n)) = Cursor::from(s).text("a").parse_with(a_or_aa).validate() {
+                return Ok((c, n + 1));
+            }
+            if let Ok((c, n)) = Cursor::from(s).text("aa").parse_with(a_or_aa).validate() {
+                return Ok((c, n + 1));
+            }
+            Cursor::from(s).text("b").validate().map(|c| (c, 0))
+        }
+        assert_eq!(a_or_aa("aaab").unwrap().1, 3);
+        let input = "a".repeat(30);
+
+        let limits = Limits::new().max_steps(10_000);
+        assert_eq!(limits.parse("aaab", a_or_aa).unwrap(), ("", 3));
+        let e = limits.parse(&input, a_or_aa).unwrap_err();
+        assert_eq!(e.is_recoverable(), false);
+        assert_eq!(
+            e.to_string(),
+            "Fatal:step budget of 10000 exhausted at offset 26"
+        );
+
+        // steps are counted whether or not matching succeeds
+        let limits = Limits::new().max_steps(3);
+        let ok = |s| {
+            Cursor::from(s)
+                .text("x")
+                .text("y")
+                .ws()
+                .validate()
+                .map(|c| (c, ()))
+        };
+        let bad = |s| {
+            Cursor::from(s)
+                .text("x")
+                .text("y")
+                .ws()
+                .text("z")
+                .validate()
+                .map(|c| (c, ()))
+        };
+        assert_eq!(limits.parse("xy z", ok).is_ok(), true);
+        assert_eq!(
+            limits.parse("xy z", bad).unwrap_err().to_string(),
+            "Fatal:step budget of 3 exhausted at offset 3"
+        );
+    }
+
+    #[test]
+    fn test_nested_limits() {
+        let words = |s| {
+            Cursor::from(s)
+                .parse_struct_vec(|s| {
+                    Cursor::from(s)
+                        .alphabetics(1..)
+                        .ws()
+                        .validate()
+                        .map(|c| (c, ()))
+                })
+                .validate()
+        };
+        let inner = |s| Limits::new().max_depth(10).parse(s, words);
+
+        // the outer budget is not reset by the inner limits, and is charged its steps
+        assert_eq!(
+            Limits::new().max_steps(3).parse("a b c d", inner).is_err(),
+            true
+        );
+        let dotted = |s| {
+            let (s, _) = inner(s)?;
+            Cursor::from(s).text(".").validate().map(|c| (c, ()))
+        };
+        assert_eq!(
+            Limits::new().max_steps(6).parse("a b.", dotted).is_ok(),
+            true
+        );
+        assert_eq!(
+            Limits::new().max_steps(5).parse("a b.", dotted).is_err(),
+            true
+        );
+
+        // a tighter inner limit applies, and fails the outer parse
+        let e = Limits::new()
+            .max_steps(100)
+            .parse("a b c d", |s| {
+                let _ = Limits::new().max_steps(2).parse(s, words);
+                Ok((s, ()))
+            })
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Fatal:step budget of 2 exhausted at offset 2"
+        );
+
+        // and nothing is carried over to later parses
+        assert_eq!(
+            Limits::new().max_steps(100).parse("a b c d", inner).is_ok(),
+            true
+        );
+        assert_eq!(inner("a b c d").is_ok(), true);
+    }
+
+    #[test]
+    fn test_max_input_len() {
+        let limits = Limits::new().max_input_len(5);
+        let word = |s| {
+            Cursor::from(s)
+                .alphabetics(1..)
+                .parse_selection::<String>()
+                .validate()
+        };
+        assert_eq!(limits.parse("hello", word).unwrap().1, "hello");
+        assert_eq!(
+            limits.parse("hello world", word).unwrap_err().to_string(),
+            "Fatal:input length 11 exceeds maximum of 5"
+        );
+
+        // also checked by Cursor::from, when called within a limited parse
+        let e = limits
+            .parse("12", |s| {
+                Cursor::from("hello world")
+                    .validate()
+                    .map(|c| (c, ()))
+                    .or(Ok((s, ())))
+            })
+            .unwrap_err();
+        assert_eq!(e.to_string(), "Fatal:input length 11 exceeds maximum of 5");
+        assert_eq!(Cursor::from("hello world").validate().is_ok(), true);
+    }
+}
@@ -1,68 +1,82 @@
-use log::Level::Trace;
-use log::{log_enabled, trace};
+use std::fmt::Debug;
 
 use crate::prelude::Matchable;
-use crate::{util, LABEL, LOG_TARGET};
-use std::fmt::Debug;
+use crate::context::ContextPath;
+use crate::span::Origin;
+use crate::tracer::{self, Event, EventKind};
 
 pub(crate) trait Loggable {
-    const LABEL_WIDTH: usize = 15;
-    const INPUT_WIDTH: usize = 35;
-    fn log_inputs<Args: Debug>(&self, msg: &str, args: Args);
-    fn log_success<Args: Debug>(&self, msg: &str, args: Args);
-    fn log_success_with_result<Args1: Debug, Args2: Debug>(&self, m: &str, args: Args1, res: Args2);
-    fn log_failure<Args: Debug, Error: Debug>(&self, msg: &str, args: Args, error: &Error);
+    fn log_inputs<Args: Debug>(&self, msg: &'static str, args: Args);
+    fn log_success<Args: Debug>(&self, msg: &'static str, args: Args);
+    fn log_success_with_result<Args1: Debug, Args2: Debug>(
+        &self,
+        m: &'static str,
+        args: Args1,
+        res: Args2,
+    );
+    fn log_failure<Args: Debug, Error: Debug>(&self, msg: &'static str, args: Args, error: &Error);
+    fn log_event(
+        &self,
+        kind: EventKind,
+        msg: &'static str,
+        args: &dyn Debug,
+        value: Option<&dyn Debug>,
+        error: Option<&dyn Debug>,
+    );
 }
 
 impl<'a, Cur> Loggable for Cur
 where
     Cur: Matchable<'a>,
 {
-    fn log_inputs<Args: Debug>(&self, msg: &str, args: Args) {
-        if log_enabled!(target: LOG_TARGET, Trace) && self.is_skip() {
-            trace!(
-                target: LOG_TARGET,
-                "{inp:<iw$} {label:<lw$} : {operation:<lw$}",
-                iw = Self::INPUT_WIDTH,
-                lw = Self::LABEL_WIDTH,
-                label = LABEL.with(|f| f.get()),
-                inp = util::formatter_str(self.str().unwrap_or_default()),
-                operation = format!("{msg}({args:?})"),
-            );
-        }
+    fn log_inputs<Args: Debug>(&self, msg: &'static str, args: Args) {
+        let kind = match self.is_skip() {
+            true => EventKind::Skip,
+            false => EventKind::Attempt,
+        };
+        self.log_event(kind, msg, &args, None, None);
     }
-    fn log_success<Args: Debug>(&self, msg: &str, args: Args) {
-        trace!(
-            target: LOG_TARGET,
-            "{inp:<iw$} {label:<lw$} : {operation:<lw$}",
-            iw = Self::INPUT_WIDTH,
-            lw = Self::LABEL_WIDTH,
-            label = LABEL.with(|f| f.get()),
-            inp = util::formatter_str(self.str().unwrap_or_default()),
-            operation = format!("{msg}({args:?})"),
-        );
+
+    fn log_success<Args: Debug>(&self, msg: &'static str, args: Args) {
+        self.log_event(EventKind::Success, msg, &args, None, None);
     }
-    fn log_success_with_result<A1: Debug, A2: Debug>(&self, msg: &str, args: A1, res: A2) {
-        trace!(
-            target: LOG_TARGET,
-            "{inp:<iw$} {label:<lw$} : {operation:<lw$} -> {res:?}",
-            iw = Self::INPUT_WIDTH,
-            lw = Self::LABEL_WIDTH,
-            label = LABEL.with(|f| f.get()),
-            inp = util::formatter_str(self.str().unwrap_or_default()),
-            operation = format!("{msg}:{args:?}"),
-        );
+
+    fn log_success_with_result<A1: Debug, A2: Debug>(&self, msg: &'static str, args: A1, res: A2) {
+        self.log_event(EventKind::Success, msg, &args, Some(&res), None);
     }
-    fn log_failure<Args: Debug, Error: Debug>(&self, msg: &str, args: Args, error: &Error) {
-        trace!(
-            target: LOG_TARGET,
-            "{inp:<iw$} {label:<lw$} : {operation:<lw$} -> {e:?}",
-            iw = Self::INPUT_WIDTH,
-            lw = Self::LABEL_WIDTH,
-            label = LABEL.with(|f| f.get()),
-            inp = util::formatter_str(self.str().unwrap_or_default()),
-            operation = format!("{msg}({args:?})"),
-            e = error,
-        );
+
+    fn log_failure<Args: Debug, Error: Debug>(&self, msg: &'static str, args: Args, error: &Error) {
+        self.log_event(EventKind::Failure, msg, &args, None, Some(error));
+    }
+
+    #[inline]
+    fn log_event(
+        &self,
+        kind: EventKind,
+        action: &'static str,
+        args: &dyn Debug,
+        value: Option<&dyn Debug>,
+        error: Option<&dyn Debug>,
+    ) {
+        tracer::dispatch(|t| {
+            let input = self.str().unwrap_or_default();
+            // a failed cursor has no input left, so is placed where it failed
+            let at = match self.is_skip() {
+                true => self.failed_at(),
+                false => Some(input),
+            };
+            let origin = self.origin().unwrap_or_else(|| Origin::of(input));
+            t.event(&Event {
+                kind,
+                context: ContextPath::current(),
+                action,
+                args,
+                input,
+                offset: at.map_or(0, |at| origin.offset(at)),
+                selection: self.selection_span(),
+                value,
+                error,
+            })
+        });
     }
 }
@@ -1,3 +1,4 @@
+#[cfg(feature = "trace")]
 use log::trace;
 
 use crate::prelude::{Cursor, ParsingError, Matchable};
@@ -42,6 +43,7 @@ pub type StrMethod<T, X> = for<'c> fn(x: &'c X, &'c str) -> Result<(&'c str, T),
 impl<'c, T> Parser<'c, Cursor<'c>, T> for StrFunc<T, ParsingError> {
     type Error = ParsingError;
     fn parse(&mut self, c: Cursor<'c>) -> Result<(Cursor<'c>, T), ParsingError> {
+        #[cfg(feature = "trace")]
         trace!("#### fn(&'b str): {s}", s = c.cur.unwrap_or("-"));
         let (s, t) = (self)(c.str()?)?;
         Ok((Cursor::from(s), t))
@@ -62,6 +64,7 @@ where
 impl<'c, T, X> Parser<'c, Cursor<'c>, T> for (&'c X, StrMethod<T, X>) {
     type Error = ParsingError;
     fn parse(&mut self, c: Cursor<'c>) -> Result<(Cursor<'c>, T), ParsingError> {
+        #[cfg(feature = "trace")]
         trace!("#### fn(context, &str): {s}", s = c.cur.unwrap_or("-"));
         let (s, t) = (self.1)(self.0, c.str()?)?;
         Ok((Cursor::from(s), t))
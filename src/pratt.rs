@@ -0,0 +1,281 @@
+use std::fmt::Debug;
+
+use crate::{limits, prelude::*};
+
+/// Associativity of an infix operator. `a - b - c` is `(a - b) - c` when left
+/// associative, and `a ^ b ^ c` is `a ^ (b ^ c)` when right associative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+}
+
+/// Passed to the fold closure of an [`ExprParser`] each time an operator has been
+/// matched along with its operand(s). The closure combines them into a new `T`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fold<T> {
+    Prefix(&'static str, T),
+    Infix(T, &'static str, T),
+    Postfix(T, &'static str),
+}
+
+/// A Pratt (precedence-climbing) parser for infix expressions.
+///
+/// Atoms are stir-style parsers (as used with `parse_with`), and operators are
+/// plain text tokens registered with a precedence. Higher precedences bind tighter.
+/// Whitespace (or whatever the `trivia` function skips) is allowed between tokens,
+/// but trailing trivia after the expression is left unconsumed.
+///
+/// Atoms typically recurse back into the expression parser to handle brackets.
+///
+/// ```
+/// use daisychain::prelude::*;
+/// use daisychain::expr::{Assoc, ExprParser, Fold};
+///
+/// fn atom(s: &str) -> Result<(&str, i64), ParsingError> {
+///     if let Ok((c, e)) = Cursor::from(s).text("(").parse_with(expr).ws().text(")").validate() {
+///         return Ok((c, e));
+///     }
+///     Cursor::from(s).digits(1..).parse_selection().validate()
+/// }
+///
+/// fn expr(s: &str) -> Result<(&str, i64), ParsingError> {
+///     ExprParser::new(atom, |f| match f {
+///         Fold::Prefix("-", a) => -a,
+///         Fold::Infix(a, "+", b) => a + b,
+///         Fold::Infix(a, "-", b) => a - b,
+///         Fold::Infix(a, "*", b) => a * b,
+///         Fold::Infix(a, "^", b) => a.pow(b as u32),
+///         _ => unreachable!(),
+///     })
+///     .prefix("-", 3)
+///     .infix("+", 1, Assoc::Left)
+///     .infix("-", 1, Assoc::Left)
+///     .infix("*", 2, Assoc::Left)
+///     .infix("^", 4, Assoc::Right)
+///     .parse(s)
+/// }
+///
+/// assert_eq!(expr("2 * (3 + 4) - -1").unwrap(), ("", 15));
+/// assert_eq!(expr("2 ^ 3 ^ 2 rest").unwrap(), (" rest", 512));
+/// ```
+pub struct ExprParser<T, A, F> {
+    atom: A,
+    fold: F,
+    trivia: fn(&str) -> &str,
+    prefix: Vec<(&'static str, u8)>,
+    infix: Vec<(&'static str, u8, Assoc)>,
+    postfix: Vec<(&'static str, u8)>,
+    pd: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T, A, F> ExprParser<T, A, F>
+where
+    T: Debug,
+    A: for<'s> Fn(&'s str) -> Result<(&'s str, T), ParsingError>,
+    F: Fn(Fold<T>) -> T,
+{
+    pub fn new(atom: A, fold: F) -> Self {
+        Self {
+            atom,
+            fold,
+            trivia: str::trim_start,
+            prefix: vec![],
+            infix: vec![],
+            postfix: vec![],
+            pd: Default::default(),
+        }
+    }
+
+    /// the function used to skip whitespace/comments between tokens (default is `str::trim_start`)
+    pub fn trivia(mut self, trivia: fn(&str) -> &str) -> Self {
+        self.trivia = trivia;
+        self
+    }
+
+    pub fn prefix(mut self, op: &'static str, precedence: u8) -> Self {
+        self.prefix.push((op, precedence));
+        self
+    }
+
+    pub fn infix(mut self, op: &'static str, precedence: u8, assoc: Assoc) -> Self {
+        self.infix.push((op, precedence, assoc));
+        self
+    }
+
+    pub fn postfix(mut self, op: &'static str, precedence: u8) -> Self {
+        self.postfix.push((op, precedence));
+        self
+    }
+
+    /// stir-style parse, so can be used with `parse_with(|s| expr_parser.parse(s))`
+    pub fn parse<'a>(&self, s: &'a str) -> Result<(&'a str, T), ParsingError> {
+        self.parse_bp(s, 0)
+    }
+
+    // binding powers are doubled precedences, the odd ones being used to
+    // break ties between operators of equal precedence
+    fn parse_bp<'a>(&self, s: &'a str, min_bp: u16) -> Result<(&'a str, T), ParsingError> {
+        let _depth = limits::enter(s)?;
+        let s = (self.trivia)(s);
+        let (mut s, mut lhs) = match longest_match(s, &self.prefix, |&(op, _)| op) {
+            Some((rest, &(op, prec))) => {
+                let (rest, rhs) = self.parse_bp(rest, 2 * prec as u16 + 1)?;
+                (rest, (self.fold)(Fold::Prefix(op, rhs)))
+            }
+            None => (self.atom)(s)?,
+        };
+
+        loop {
+            let t = (self.trivia)(s);
+            let postfix = longest_match(t, &self.postfix, |&(op, _)| op);
+            let infix = longest_match(t, &self.infix, |&(op, _, _)| op);
+            // the longest operator of either kind wins, so infix "!=" is preferred to postfix "!"
+            let infix = match (postfix, infix) {
+                (Some((p, _)), Some((i, _))) if p.len() <= i.len() => None,
+                (_, infix) => infix,
+            };
+            if let (Some((rest, &(op, prec))), None) = (postfix, infix) {
+                if (2 * prec as u16) < min_bp {
+                    break;
+                }
+                lhs = (self.fold)(Fold::Postfix(lhs, op));
+                s = rest;
+                continue;
+            }
+            if let Some((rest, &(op, prec, assoc))) = infix {
+                let (lbp, rbp) = match assoc {
+                    Assoc::Left => (2 * prec as u16, 2 * prec as u16 + 1),
+                    Assoc::Right => (2 * prec as u16 + 1, 2 * prec as u16),
+                };
+                if lbp < min_bp {
+                    break;
+                }
+                let (rest, rhs) = self.parse_bp(rest, rbp)?;
+                lhs = (self.fold)(Fold::Infix(lhs, op, rhs));
+                s = rest;
+                continue;
+            }
+            break;
+        }
+        Ok((s, lhs))
+    }
+}
+
+/// longest operator wins, so that "**" is preferred to "*"
+fn longest_match<'a, 'o, O>(
+    s: &'a str,
+    ops: &'o [O],
+    text: impl Fn(&O) -> &'static str,
+) -> Option<(&'a str, &'o O)> {
+    ops.iter()
+        .filter(|o| s.starts_with(text(o)))
+        .max_by_key(|o| text(o).len())
+        .map(|o| (&s[text(o).len()..], o))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[derive(Debug, PartialEq)]
+    enum Ast {
+        Num(i32),
+        Var(String),
+        Unary(&'static str, Box<Ast>),
+        Binary(Box<Ast>, &'static str, Box<Ast>),
+    }
+
+    impl std::fmt::Display for Ast {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            match self {
+                Ast::Num(n) => write!(f, "{n}"),
+                Ast::Var(v) => write!(f, "{v}"),
+                Ast::Unary(op, a) if *op == "!" => write!(f, "({a}{op})"),
+                Ast::Unary(op, a) => write!(f, "({op}{a})"),
+                Ast::Binary(a, op, b) => write!(f, "({a} {op} {b})"),
+            }
+        }
+    }
+
+    fn atom(s: &str) -> Result<(&str, Ast), ParsingError> {
+        if let Ok((c, e)) = Cursor::from(s)
+            .text("(")
+            .parse_with(expr)
+            .ws()
+            .text(")")
+            .validate()
+        {
+            return Ok((c, e));
+        }
+        if let Ok((c, i)) = Cursor::from(s).digits(1..).parse_selection().validate() {
+            return Ok((c, Ast::Num(i)));
+        }
+        let (c, v) = Cursor::from(s)
+            .alphabetics(1..)
+            .parse_selection()
+            .validate()?;
+        Ok((c, Ast::Var(v)))
+    }
+
+    fn expr(s: &str) -> Result<(&str, Ast), ParsingError> {
+        ExprParser::new(atom, |f| match f {
+            Fold::Prefix(op, a) | Fold::Postfix(a, op) => Ast::Unary(op, Box::new(a)),
+            Fold::Infix(a, op, b) => Ast::Binary(Box::new(a), op, Box::new(b)),
+        })
+        .prefix("-", 5)
+        .postfix("!", 7)
+        .infix("=", 1, Assoc::Right)
+        .infix("!=", 1, Assoc::Left)
+        .infix("+", 2, Assoc::Left)
+        .infix("-", 2, Assoc::Left)
+        .infix("*", 3, Assoc::Left)
+        .infix("**", 6, Assoc::Right)
+        .parse(s)
+    }
+
+    fn render(s: &str) -> String {
+        expr(s).unwrap().1.to_string()
+    }
+
+    #[test]
+    fn test_precedence_and_assoc() {
+        assert_eq!(render("1"), "1");
+        assert_eq!(render("1 + 2 * 3"), "(1 + (2 * 3))");
+        assert_eq!(render("1 * 2 + 3"), "((1 * 2) + 3)");
+        assert_eq!(render("1 - 2 - 3"), "((1 - 2) - 3)");
+        assert_eq!(render("a = b = c"), "(a = (b = c))");
+        assert_eq!(render("2 ** 3 ** 4"), "(2 ** (3 ** 4))");
+        assert_eq!(render("2*3**4"), "(2 * (3 ** 4))");
+        assert_eq!(render("(1 + 2) * 3"), "((1 + 2) * 3)");
+    }
+
+    #[test]
+    fn test_prefix_and_postfix() {
+        assert_eq!(render("-1 - -2"), "((-1) - (-2))");
+        assert_eq!(render("-a * b"), "((-a) * b)");
+        assert_eq!(render("-a ** b"), "(-(a ** b))");
+        assert_eq!(render("-n!"), "(-(n!))");
+        assert_eq!(render("n! * 2"), "((n!) * 2)");
+        // the longest operator wins, whether postfix or infix
+        assert_eq!(render("a != b"), "(a != b)");
+        assert_eq!(render("n! != 2"), "((n!) != 2)");
+        assert_eq!(render("n!!= 2"), "((n!) != 2)");
+    }
+
+    #[test]
+    fn test_cursor_position() {
+        assert_eq!(expr("1 + 2 )").unwrap().0, " )");
+        assert_eq!(expr("1 +").is_err(), true);
+        assert_eq!(expr("* 1").is_err(), true);
+
+        let (c, ast) = Cursor::from("x = 1 + y;")
+            .parse_with(expr)
+            .text(";")
+            .validate()
+            .unwrap();
+        assert_eq!(c, "");
+        assert_eq!(ast.to_string(), "(x = (1 + y))");
+    }
+}
@@ -1,9 +1,17 @@
-pub use crate::text_parser::{Bind, Matchable, Selectable};
-pub use crate::cursor::Cursor;
+pub use crate::text_parser::{Bind, FromStrRadix, MapLast, Matchable, Selectable};
+pub use crate::cursor::{Captures, Cursor};
 pub use crate::error::ParsingError;
+pub use crate::limits::{LimitError, Limits};
+pub use crate::span::{Span, Spanned};
 
+/// Parsers built before they are run, in two styles. `Seq` (with `Lazy` and `RunLazy`)
+/// records the same methods as the `Cursor` API and replays them on each input. The
+/// `combo` parsers (`Parser`, `StrParser` and `SP`) are composed from parser values with
+/// `chain_parser`. A `Seq` is also a `combo::Parser`, so the two can be mixed, but `Seq`
+/// covers far more of the cursor API and is usually the one to reach for.
 pub mod lazy {
     pub use crate::combo::Parser;
     pub use crate::combo::StrParser;
     pub use crate::combo::SP;
+    pub use crate::lazy::{Capture, Lazy, RunLazy, Selection, SelectionStr, Seq, Then, With};
 }
@@ -0,0 +1,377 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fmt,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::context;
+use crate::tracer::{Event, EventKind, Tracer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SectionKind {
+    /// a named [`Grammar`](crate::grammar::Grammar) rule
+    Rule,
+    /// a `debug_context` label
+    Context,
+}
+
+/// timings and counts for a single rule or `debug_context` label
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SectionStats {
+    pub invocations: usize,
+    pub successes: usize,
+    pub failures: usize,
+    /// bytes consumed by successful invocations
+    pub bytes: usize,
+    /// wall time including nested rules and labels
+    pub inclusive: Duration,
+    /// wall time excluding nested rules and labels
+    pub exclusive: Duration,
+}
+
+/// per-section statistics, slowest (inclusive) first, which display as a table
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+    pub sections: Vec<(SectionKind, String, SectionStats)>,
+}
+
+impl Profile {
+    pub fn get(&self, kind: SectionKind, name: &str) -> Option<&SectionStats> {
+        self.sections
+            .iter()
+            .find(|(k, n, _)| *k == kind && n == name)
+            .map(|(_, _, s)| s)
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>8} {:>8} {:>8} {:>10} {:>12} {:>12}",
+            "section", "calls", "ok", "failed", "bytes", "incl (µs)", "excl (µs)"
+        )?;
+        for (kind, name, s) in &self.sections {
+            let name = match kind {
+                SectionKind::Rule => format!("rule {name}"),
+                SectionKind::Context => name.clone(),
+            };
+            writeln!(
+                f,
+                "{name:<24} {calls:>8} {ok:>8} {failed:>8} {bytes:>10} {incl:>12} {excl:>12}",
+                calls = s.invocations,
+                ok = s.successes,
+                failed = s.failures,
+                bytes = s.bytes,
+                incl = s.inclusive.as_micros(),
+                excl = s.exclusive.as_micros(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+struct Frame {
+    kind: SectionKind,
+    name: String,
+    // for labels, the context path including the label
+    path: String,
+    start: Instant,
+    start_offset: usize,
+    end_offset: usize,
+    failed: bool,
+    nested: Duration,
+}
+
+impl Frame {
+    // whether an event with this context path is within the label
+    fn contains(&self, path: &str) -> bool {
+        context::is_within(path, &self.path)
+    }
+}
+
+/// A [`Tracer`] recording invocation counts and wall times for each grammar rule
+/// and `debug_context` label.
+///
+/// A label lasts from its `debug_context` until the parser that set it returns, or sets
+/// another label. It fails if a match directly within it failed, or the rule it is within
+/// failed, and otherwise succeeds. As timings are taken from trace events, they include
+/// the overhead of tracing itself. Profiling needs the `trace` feature: without it no
+/// events are generated, and the profile stays empty.
+///
+/// ```
+/// use daisychain::prelude::*;
+/// use daisychain::trace::{with_tracer, Profiler, SectionKind};
+///
+/// fn key_value(s: &str) -> Result<(&str, &str), ParsingError> {
+///     Cursor::from(s)
+///         .debug_context("key_value")
+///         .alphabetics(1..)
+///         .parse_selection_as_str()
+///         .text(";")
+///         .validate()
+/// }
+///
+/// let profiler = Profiler::new();
+/// let mut keys = vec![];
+/// with_tracer(profiler.clone(), || {
+///     Cursor::from("a;bc;d")
+///         .debug_context("list")
+///         .parse_struct_vec_to(key_value, &mut keys)
+/// })
+/// .unwrap();
+///
+/// let profile = profiler.profile();
+/// println!("{profile}");
+/// let stats = profile.get(SectionKind::Context, "key_value");
+/// #[cfg(feature = "trace")]
+/// {
+///     let stats = stats.unwrap();
+///     assert_eq!((stats.invocations, stats.successes, stats.failures), (3, 2, 1));
+///     assert_eq!(stats.bytes, 5);
+/// }
+/// #[cfg(not(feature = "trace"))]
+/// assert!(stats.is_none());
+/// ```
+#[derive(Default)]
+pub struct Profiler {
+    frames: RefCell<Vec<Frame>>,
+    last: Cell<Option<Instant>>,
+    stats: RefCell<BTreeMap<(SectionKind, String), SectionStats>>,
+}
+
+impl Profiler {
+    pub fn new() -> Rc<Self> {
+        Rc::default()
+    }
+
+    /// the statistics so far, with any labels still in progress ending at the last event
+    pub fn profile(&self) -> Profile {
+        let last = self.last.get().unwrap_or_else(Instant::now);
+        while self.close_top(last, None) {}
+        let mut sections: Vec<_> = self
+            .stats
+            .borrow()
+            .iter()
+            .map(|((kind, name), s)| (*kind, name.clone(), *s))
+            .collect();
+        sections.sort_by_key(|s| std::cmp::Reverse(s.2.inclusive));
+        Profile { sections }
+    }
+
+    pub fn clear(&self) {
+        self.frames.borrow_mut().clear();
+        self.last.set(None);
+        self.stats.borrow_mut().clear();
+    }
+
+    // ends labels which do not contain `path`
+    fn close_labels(&self, now: Instant, path: &str) {
+        loop {
+            let open = match self.frames.borrow().last() {
+                Some(f) => f.kind == SectionKind::Rule || f.contains(path),
+                None => true,
+            };
+            if open {
+                return;
+            }
+            self.close_top(now, None);
+        }
+    }
+
+    // ends the innermost section, a rule's `failed` being given by its Exit event
+    fn close_top(&self, now: Instant, exit: Option<(usize, bool)>) -> bool {
+        let Some(frame) = self.frames.borrow_mut().pop() else {
+            return false;
+        };
+        let (end_offset, failed) = exit.unwrap_or((frame.end_offset, frame.failed));
+        let elapsed = now.duration_since(frame.start);
+        let recursive = self
+            .frames
+            .borrow()
+            .iter()
+            .any(|f| f.kind == frame.kind && f.name == frame.name);
+        if let Some(parent) = self.frames.borrow_mut().last_mut() {
+            parent.nested += elapsed;
+            if !failed {
+                parent.end_offset = parent.end_offset.max(end_offset);
+            }
+        }
+
+        let mut stats = self.stats.borrow_mut();
+        let s = stats.entry((frame.kind, frame.name)).or_default();
+        s.invocations += 1;
+        if failed {
+            s.failures += 1;
+        } else {
+            s.successes += 1;
+            s.bytes += end_offset.saturating_sub(frame.start_offset);
+        }
+        if !recursive {
+            s.inclusive += elapsed;
+        }
+        s.exclusive += elapsed.saturating_sub(frame.nested);
+        true
+    }
+
+    fn open(&self, now: Instant, kind: SectionKind, name: String, e: &Event) {
+        self.frames.borrow_mut().push(Frame {
+            kind,
+            name,
+            path: e.context.to_string(),
+            start: now,
+            start_offset: e.offset,
+            end_offset: e.offset,
+            failed: false,
+            nested: Duration::ZERO,
+        });
+    }
+}
+
+impl Tracer for Profiler {
+    fn event(&self, e: &Event) {
+        let now = Instant::now();
+        let context = e.context.to_string();
+        self.last.set(Some(now));
+        match e.kind {
+            EventKind::Enter => {
+                self.close_labels(now, &context);
+                self.open(now, SectionKind::Rule, e.action.to_string(), e);
+            }
+            EventKind::Exit => {
+                // labels set within the rule end with it, and any error passed through them
+                loop {
+                    match self.frames.borrow_mut().last_mut() {
+                        Some(f) if f.kind == SectionKind::Context => f.failed |= e.error.is_some(),
+                        _ => break,
+                    }
+                    self.close_top(now, None);
+                }
+                self.close_top(now, Some((e.offset, e.error.is_some())));
+            }
+            EventKind::Context => {
+                // the parser's previous label (perhaps the same one) has ended
+                loop {
+                    let open = match self.frames.borrow().last() {
+                        Some(f) => {
+                            f.kind == SectionKind::Rule
+                                || f.path != context && f.contains(&context)
+                        }
+                        None => true,
+                    };
+                    if open {
+                        break;
+                    }
+                    self.close_top(now, None);
+                }
+                let label = context.rsplit(" > ").next().unwrap_or_default();
+                self.open(now, SectionKind::Context, label.to_string(), e);
+            }
+            kind => {
+                self.close_labels(now, &context);
+                if let Some(f) = self.frames.borrow_mut().last_mut() {
+                    if f.kind == SectionKind::Context && f.path == context {
+                        match kind {
+                            EventKind::Failure | EventKind::Skip => f.failed = true,
+                            EventKind::Success => f.end_offset = f.end_offset.max(e.offset),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::Grammar;
+    use crate::prelude::*;
+    use crate::tracer::with_tracer;
+    use test_log::test;
+
+    #[test]
+    fn test_profile_labels() {
+        fn key_value(s: &str) -> Result<(&str, &str), ParsingError> {
+            Cursor::from(s)
+                .debug_context("key_value")
+                .alphabetics(1..)
+                .parse_selection_as_str()
+                .text(";")
+                .validate()
+        }
+
+        let profiler = Profiler::new();
+        let mut keys = vec![];
+        with_tracer(profiler.clone(), || {
+            Cursor::from("a;bc;d")
+                .debug_context("list")
+                .parse_struct_vec_to(key_value, &mut keys)
+        })
+        .unwrap();
+        assert_eq!(keys, ["a", "bc"]);
+
+        let profile = profiler.profile();
+        let stats = profile.get(SectionKind::Context, "key_value").unwrap();
+        assert_eq!((stats.invocations, stats.successes, stats.failures), (3, 2, 1));
+        assert_eq!(stats.bytes, 5);
+        let list = profile.get(SectionKind::Context, "list").unwrap();
+        assert_eq!((list.invocations, list.successes, list.bytes), (1, 1, 5));
+        assert!(list.inclusive >= stats.inclusive);
+    }
+
+    #[test]
+    fn test_profile_rules() {
+        let g = Grammar::new()
+            .rule("num", |_g, s| {
+                Cursor::from(s)
+                    .debug_context("digits")
+                    .digits(1..)
+                    .parse_selection()
+                    .validate()
+            })
+            .rule("sum", |g, s| {
+                let (c, a, b) = Cursor::from(s)
+                    .debug_context("sum")
+                    .parse_with(|s| g.parse("num", s))
+                    .text("+")
+                    .parse_with(|s| g.parse("num", s))
+                    .validate()?;
+                Ok((c, a + b))
+            });
+        let profiler = Profiler::new();
+        let res = with_tracer(profiler.clone(), || {
+            (g.parse("sum", "12+345"), g.parse("sum", "1+x"))
+        });
+        assert_eq!(res.0.unwrap(), ("", 357));
+        assert_eq!(res.1.is_err(), true);
+
+        let profile = profiler.profile();
+        let num = profile.get(SectionKind::Rule, "num").unwrap();
+        assert_eq!((num.invocations, num.successes, num.failures), (4, 3, 1));
+        assert_eq!(num.bytes, 6);
+        let sum = profile.get(SectionKind::Rule, "sum").unwrap();
+        assert_eq!((sum.invocations, sum.successes, sum.failures), (2, 1, 1));
+        assert_eq!(sum.bytes, 6);
+        assert!(sum.inclusive >= sum.exclusive);
+        assert!(sum.inclusive >= num.inclusive);
+
+        let digits = profile.get(SectionKind::Context, "digits").unwrap();
+        assert_eq!((digits.invocations, digits.failures), (4, 1));
+        let label = profile.get(SectionKind::Context, "sum").unwrap();
+        assert_eq!(
+            (label.invocations, label.successes, label.failures),
+            (2, 1, 1)
+        );
+
+        let table = profile.to_string();
+        assert_eq!(table.lines().count(), 5, "{table}");
+        assert!(table.contains("rule sum "), "{table}");
+
+        profiler.clear();
+        assert_eq!(profiler.profile(), Profile::default());
+    }
+}
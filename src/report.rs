@@ -0,0 +1,371 @@
+use std::fmt::Write;
+
+use crate::context;
+use crate::tracer::{EventKind, Record};
+
+// characters of input shown either side of the cursor
+const BEFORE: usize = 30;
+const AFTER: usize = 50;
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; font-size: 14px; margin: 1em 2em; }
+pre.input { background: #f4f4f4; padding: 0.5em; white-space: pre-wrap; }
+details { margin-left: 1.5em; border-left: 1px dotted #aaa; padding-left: 0.5em; }
+summary { cursor: pointer; font-weight: bold; }
+summary.failure { color: #b00020; }
+.step { font-family: monospace; white-space: pre; padding: 1px 4px; }
+.step .offset { display: inline-block; width: 4em; text-align: right; color: #888; }
+.step .kind { display: inline-block; width: 6em; }
+.step .action { display: inline-block; min-width: 18em; }
+.step code { background: #f4f4f4; }
+.step mark { background: #fff176; }
+.step .cursor { color: #1565c0; font-weight: bold; }
+.success { background: #e8f5e9; }
+.failure { background: #ffebee; }
+.skip { color: #999; }
+.attempt { color: #555; }
+.context > summary { color: #1565c0; }
+"#;
+
+/// Renders a parse trace as a standalone HTML page.
+///
+/// `input` is the text that was parsed and `records` the events captured with a
+/// [`Recorder`](crate::trace::Recorder). Each step shows the input around the cursor,
+/// with any selection highlighted. Grammar rules and `debug_context` sections are
+/// collapsible, sections nesting as the context path of each step does, and steps are
+/// coloured by success or failure.
+///
+/// ```
+/// use daisychain::prelude::*;
+/// use daisychain::trace::{html_report, with_tracer, Recorder};
+///
+/// let rec = Recorder::new();
+/// let input = "ab12";
+/// let _ = with_tracer(rec.clone(), || {
+///     Cursor::from(input)
+///         .debug_context("pair")
+///         .alphabetics(1..)
+///         .digits(1..)
+///         .validate()
+/// });
+/// let html = html_report(input, &rec.records());
+/// assert!(html.starts_with("<!DOCTYPE html>"));
+/// // std::fs::write("trace.html", html)?;
+/// ```
+pub fn html_report(input: &str, records: &[Record]) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>daisychain parse trace</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>Parse trace</h1>\n<pre class=\"input\">{}</pre>\n",
+        escape(input)
+    );
+    let _ = writeln!(
+        html,
+        "<p>{} events, {} failures</p>",
+        records.len(),
+        records
+            .iter()
+            .filter(|r| r.kind == EventKind::Failure)
+            .count()
+    );
+
+    // open sections, the context path of a debug_context, or None for a grammar rule
+    let mut open: Vec<Option<&str>> = vec![];
+    // ends the labels on top which the context path is not within, or is a sibling of
+    let close_labels = |html: &mut String, open: &mut Vec<Option<&str>>, path: &str, sibling| {
+        while let Some(Some(label)) = open.last() {
+            if context::is_within(path, label) && !(sibling && path == *label) {
+                break;
+            }
+            open.pop();
+            html.push_str("</details>\n");
+        }
+    };
+    let failed = failed_rules(records);
+    for (i, r) in records.iter().enumerate() {
+        match r.kind {
+            EventKind::Context => {
+                close_labels(&mut html, &mut open, &r.context, true);
+                let _ = writeln!(
+                    html,
+                    "<details class=\"context\" open><summary>{}</summary>",
+                    escape(r.args.trim_matches('"'))
+                );
+                open.push(Some(&r.context));
+            }
+            EventKind::Enter => {
+                close_labels(&mut html, &mut open, &r.context, false);
+                let class = match failed[i] {
+                    true => "failure",
+                    false => "success",
+                };
+                let _ = writeln!(
+                    html,
+                    "<details class=\"rule\" open><summary class=\"{class}\">{} @ {}</summary>",
+                    escape(r.action),
+                    r.offset
+                );
+                open.push(None);
+            }
+            EventKind::Exit => {
+                while let Some(Some(_)) = open.last() {
+                    open.pop();
+                    html.push_str("</details>\n");
+                }
+                write_step(&mut html, input, r);
+                if open.pop().is_some() {
+                    html.push_str("</details>\n");
+                }
+            }
+            _ => {
+                close_labels(&mut html, &mut open, &r.context, false);
+                write_step(&mut html, input, r);
+            }
+        }
+    }
+    for _ in open {
+        html.push_str("</details>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+// for each Enter record, whether its matching Exit (allowing for recursion) failed
+fn failed_rules(records: &[Record]) -> Vec<bool> {
+    let mut failed = vec![false; records.len()];
+    let mut enters = vec![];
+    for (i, r) in records.iter().enumerate() {
+        match r.kind {
+            EventKind::Enter => enters.push(i),
+            EventKind::Exit => {
+                if let Some(enter) = enters.pop() {
+                    failed[enter] = r.error.is_some();
+                }
+            }
+            _ => {}
+        }
+    }
+    failed
+}
+
+fn write_step(html: &mut String, input: &str, r: &Record) {
+    let class = match (r.kind, &r.error) {
+        (EventKind::Exit, None) => "success",
+        (EventKind::Exit, Some(_)) => "failure",
+        (EventKind::Success, _) => "success",
+        (EventKind::Failure, _) => "failure",
+        (EventKind::Skip, _) => "skip",
+        _ => "attempt",
+    };
+    let action = match r.kind {
+        EventKind::Exit => format!("exit {}", r.action),
+        _ => format!("{}({})", r.action, r.args),
+    };
+    let result = r
+        .value
+        .as_ref()
+        .or(r.error.as_ref())
+        .map(|v| format!(" -&gt; {}", escape(v)))
+        .unwrap_or_default();
+    let _ = writeln!(
+        html,
+        "<div class=\"step {class}\"><span class=\"offset\">{}</span> <span class=\"kind\">{:?}</span> <span class=\"action\">{}</span> <code>{}</code>{result}</div>",
+        r.offset,
+        r.kind,
+        escape(&action),
+        input_at(input, r)
+    );
+}
+
+// the input around the cursor, with the cursor and selection marked
+fn input_at(input: &str, r: &Record) -> String {
+    let cursor = floor(input, r.offset);
+    let from = floor(input, cursor.saturating_sub(BEFORE));
+    let to = floor(input, cursor + AFTER);
+    let (sel_start, sel_end) = match r.selection {
+        Some(span) => (floor(input, span.start), floor(input, span.end)),
+        None => (cursor, cursor),
+    };
+
+    let mut s = String::new();
+    if from > 0 {
+        s.push('…');
+    }
+    let mut bounds = vec![
+        from,
+        to,
+        cursor,
+        sel_start.clamp(from, to),
+        sel_end.clamp(from, to),
+    ];
+    bounds.sort_unstable();
+    bounds.dedup();
+    for w in bounds.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        if a == cursor {
+            s.push_str("<span class=\"cursor\">|</span>");
+        }
+        let text = input[a..b].escape_debug().to_string();
+        let text = escape(&text.replace("\\\"", "\"").replace("\\\'", "\'"));
+        match a >= sel_start && b <= sel_end {
+            true => {
+                let _ = write!(s, "<mark>{text}</mark>");
+            }
+            false => s.push_str(&text),
+        }
+    }
+    if cursor == to {
+        s.push_str("<span class=\"cursor\">|</span>");
+    }
+    if to < input.len() {
+        s.push('…');
+    }
+    s
+}
+
+// the largest char boundary of `s` at or below `i`
+fn floor(s: &str, i: usize) -> usize {
+    let mut i = i.min(s.len());
+    while !s.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::Grammar;
+    use crate::prelude::*;
+    use crate::tracer::{with_tracer, Recorder};
+    use test_log::test;
+
+    #[test]
+    fn test_input_at() {
+        let r = |offset, selection| Record {
+            kind: EventKind::Success,
+            context: String::new(),
+            action: "digits",
+            args: String::new(),
+            offset,
+            selection,
+            value: None,
+            error: None,
+        };
+        assert_eq!(
+            input_at("ab12", &r(2, None)),
+            "ab<span class=\"cursor\">|</span>12"
+        );
+        assert_eq!(
+            input_at("ab12", &r(4, Some(Span::new(2, 4)))),
+            "ab<mark>12</mark><span class=\"cursor\">|</span>"
+        );
+        assert_eq!(
+            input_at("a<é", &r(2, None)),
+            "a&lt;<span class=\"cursor\">|</span>é"
+        );
+        let long = "x".repeat(100);
+        let s = input_at(&long, &r(40, None));
+        assert!(s.starts_with('…') && s.ends_with('…'), "{s}");
+    }
+
+    #[test]
+    fn test_html_report() {
+        let g = Grammar::new().rule("num", |_g, s| {
+            Cursor::from(s)
+                .debug_context("num")
+                .digits(1..)
+                .parse_selection::<u32>()
+                .validate()
+        });
+        let rec = Recorder::new();
+        let input = "12<x";
+        let _ = with_tracer(rec.clone(), || {
+            Cursor::from(input)
+                .parse_with(|s| g.parse("num", s))
+                .text("+")
+                .validate()
+        });
+        let html = html_report(input, &rec.records());
+        assert!(
+            html.contains("<pre class=\"input\">12&lt;x</pre>"),
+            "{html}"
+        );
+        assert!(
+            html.contains("<summary class=\"success\">num @ 0</summary>"),
+            "{html}"
+        );
+        assert!(
+            html.contains("<details class=\"context\" open><summary>num</summary>"),
+            "{html}"
+        );
+        assert!(html.contains("<div class=\"step failure\">"), "{html}");
+        assert!(html.contains("<mark>12</mark>"), "{html}");
+        assert_eq!(
+            html.matches("<details").count(),
+            html.matches("</details>").count()
+        );
+    }
+
+    #[test]
+    fn test_nested_contexts() {
+        let rec = Recorder::new();
+        let input = "ab12cd";
+        let _ = with_tracer(rec.clone(), || {
+            Cursor::from(input)
+                .debug_context("outer")
+                .alphabetics(1..)
+                .parse_with(|s| {
+                    Cursor::from(s)
+                        .debug_context("inner")
+                        .digits(1..)
+                        .parse_selection_as_str()
+                        .validate()
+                })
+                .text("cd")
+                .validate()
+        });
+        let html = html_report(input, &rec.records());
+        let at = |needle: &str| {
+            html.find(needle)
+                .unwrap_or_else(|| panic!("{needle} in {html}"))
+        };
+        // inner is within outer, and the step after the nested parse returns is back in outer
+        let inner = at("<summary>inner</summary>");
+        let inner_end = inner + html[inner..].find("</details>").unwrap();
+        assert!(at("<summary>outer</summary>") < inner);
+        assert!(at("digits") < inner_end, "{html}");
+        assert!(at("text(") > inner_end, "{html}");
+        assert_eq!(html[inner_end..].matches("</details>").count(), 2, "{html}");
+    }
+
+    #[test]
+    fn test_failed_rules() {
+        let record = |kind, error: Option<&str>| Record {
+            kind,
+            context: String::new(),
+            action: "expr",
+            args: String::new(),
+            offset: 0,
+            selection: None,
+            value: None,
+            error: error.map(str::to_string),
+        };
+        // an outer rule failing after a recursive call to it succeeded
+        let records = [
+            record(EventKind::Enter, None),
+            record(EventKind::Enter, None),
+            record(EventKind::Exit, None),
+            record(EventKind::Attempt, None),
+            record(EventKind::Exit, Some("NoMatch")),
+        ];
+        assert_eq!(failed_rules(&records), [true, false, false, false, false]);
+    }
+}
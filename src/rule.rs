@@ -0,0 +1,590 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt,
+};
+
+#[cfg(feature = "trace")]
+use log::trace;
+
+#[cfg(feature = "trace")]
+use crate::LOG_TARGET;
+use crate::{
+    limits,
+    prelude::*,
+    tracer::{self, EventKind},
+};
+
+type RuleFn<'g, T> =
+    Box<dyn for<'s> Fn(&Grammar<'g, T>, &'s str) -> Result<(&'s str, T), ParsingError> + 'g>;
+
+/// Fatal errors raised by a [`Grammar`], rather than by the rules themselves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrammarError {
+    UnknownRule(&'static str),
+    LeftRecursion { rule: &'static str, offset: usize },
+}
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownRule(rule) => write!(f, "unknown rule '{rule}'"),
+            Self::LeftRecursion { rule, offset } => write!(
+                f,
+                "left recursion in rule '{rule}' at offset {offset} (see Grammar::allow_left_recursion)"
+            ),
+        }
+    }
+}
+
+impl Error for GrammarError {}
+
+impl From<GrammarError> for ParsingError {
+    fn from(e: GrammarError) -> Self {
+        ParsingError::Fatal(Some(Box::new(e)))
+    }
+}
+
+enum Memo<T> {
+    /// rule is being evaluated at this offset, with a failure as the seed for left recursion
+    Seed {
+        left_recursive: bool,
+    },
+    Done(Result<(usize, T), ParsingError>),
+}
+
+/// A set of named, mutually recursive rules.
+///
+/// Each rule is a stir-style parser which additionally receives the grammar, so
+/// that it can invoke other rules (or itself) by name with [`Grammar::parse`].
+/// All rules produce the same type `T`, typically an AST node.
+///
+/// A rule which invokes itself without consuming any input is left-recursive. By default
+/// this is detected and the whole parse fails with a fatal [`GrammarError::LeftRecursion`],
+/// even if the rule swallowed the error and tried another alternative.
+/// With [`Grammar::allow_left_recursion`] rule results are memoised by offset, and
+/// left-recursive rules are evaluated by "growing the seed": the rule is re-run
+/// for as long as it consumes more input than the previous attempt.
+///
+/// Grammars with heavy backtracking can opt in to packrat parsing with [`Grammar::memoise`],
+/// and [`Grammar::stats`] reports how often each rule was invoked and answered from the memo.
+///
+/// ```
+/// use daisychain::prelude::*;
+/// use daisychain::grammar::Grammar;
+///
+/// // expr := expr '-' num | num
+/// let g = Grammar::new()
+///     .rule("expr", |g, s| {
+///         if let Ok((c, a, b)) = Cursor::from(s)
+///             .parse_with(|s| g.parse("expr", s))
+///             .text("-")
+///             .parse_with(|s| g.parse("num", s))
+///             .validate()
+///         {
+///             return Ok((c, a - b));
+///         }
+///         g.parse("num", s)
+///     })
+///     .rule("num", |_g, s| Cursor::from(s).digits(1..).parse_selection().validate());
+///
+/// assert!(g.parse("expr", "9-3-2").is_err());
+///
+/// let g = g.allow_left_recursion();
+/// assert_eq!(g.parse("expr", "9-3-2").unwrap(), ("", 4));
+/// ```
+pub struct Grammar<'g, T> {
+    rules: HashMap<&'static str, RuleFn<'g, T>>,
+    left_recursion: bool,
+    memoise: bool,
+    depth: Cell<usize>,
+    origin: Cell<usize>,
+    active: RefCell<HashSet<(&'static str, usize)>>,
+    memo: RefCell<HashMap<(&'static str, usize), Memo<T>>>,
+    fatal: RefCell<Option<GrammarError>>,
+    stats: RefCell<HashMap<&'static str, RuleStats>>,
+}
+
+impl<'g, T> Default for Grammar<'g, T> {
+    fn default() -> Self {
+        Self {
+            rules: HashMap::new(),
+            left_recursion: false,
+            memoise: false,
+            depth: Cell::new(0),
+            origin: Cell::new(0),
+            active: RefCell::default(),
+            memo: RefCell::default(),
+            fatal: RefCell::default(),
+            stats: RefCell::default(),
+        }
+    }
+}
+
+impl<'g, T: Clone> Grammar<'g, T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds (or replaces) a named rule
+    pub fn rule<F>(mut self, name: &'static str, f: F) -> Self
+    where
+        F: for<'s> Fn(&Grammar<'g, T>, &'s str) -> Result<(&'s str, T), ParsingError> + 'g,
+    {
+        self.rules.insert(name, Box::new(f));
+        self
+    }
+
+    /// memoise rule results and support left-recursive rules by seed-growing
+    pub fn allow_left_recursion(mut self) -> Self {
+        self.left_recursion = true;
+        self
+    }
+
+    /// packrat parsing: cache the result of each rule at each offset, so that
+    /// backtracking never re-parses the same rule at the same position twice.
+    /// Errors with a source (which a clone would lose) are not cached, and are re-parsed
+    pub fn memoise(mut self) -> Self {
+        self.memoise = true;
+        self
+    }
+
+    /// counts of rule invocations (and memo hits) accumulated across parses
+    pub fn stats(&self) -> MemoStats {
+        let mut rules: Vec<_> = self.stats.borrow().iter().map(|(&r, &s)| (r, s)).collect();
+        rules.sort_by_key(|&(r, _)| r);
+        MemoStats { rules }
+    }
+
+    pub fn reset_stats(&self) {
+        self.stats.borrow_mut().clear();
+    }
+
+    /// invoke a rule by name. Stir-style, so can be used with `parse_with(|s| g.parse("rule", s))`
+    pub fn parse<'s>(&self, rule: &'static str, s: &'s str) -> Result<(&'s str, T), ParsingError> {
+        let top_level = self.depth.get() == 0;
+        if top_level {
+            self.origin.set(s.as_ptr() as usize);
+            self.active.borrow_mut().clear();
+            self.memo.borrow_mut().clear();
+            self.fatal.borrow_mut().take();
+        }
+        let _depth = limits::enter(s)?;
+        tracer::rule_event(EventKind::Enter, rule, s, self.offset(s), None);
+        self.depth.set(self.depth.get() + 1);
+        self.stats.borrow_mut().entry(rule).or_default().calls += 1;
+        let res = if self.memoise || self.left_recursion {
+            self.parse_memoised(rule, s)
+        } else {
+            self.parse_detecting(rule, s)
+        };
+        self.depth.set(self.depth.get() - 1);
+        match &res {
+            Ok((rest, _)) => tracer::rule_event(EventKind::Exit, rule, rest, self.offset(rest), None),
+            Err(e) => tracer::rule_event(EventKind::Exit, rule, s, self.offset(s), Some(e)),
+        }
+
+        if top_level {
+            self.memo.borrow_mut().clear();
+            if let Some(e) = self.fatal.borrow_mut().take() {
+                return Err(e.into());
+            }
+        }
+        res
+    }
+
+    fn offset(&self, s: &str) -> usize {
+        (s.as_ptr() as usize).wrapping_sub(self.origin.get())
+    }
+
+    fn invoke<'s>(&self, rule: &'static str, s: &'s str) -> Result<(&'s str, T), ParsingError> {
+        match self.rules.get(rule) {
+            Some(f) => f(self, s),
+            None => {
+                let e = GrammarError::UnknownRule(rule);
+                self.fatal.borrow_mut().get_or_insert(e.clone());
+                Err(e.into())
+            }
+        }
+    }
+
+    #[cfg_attr(not(feature = "trace"), allow(unused_variables))]
+    fn left_recursion_failure(&self, rule: &'static str, offset: usize) -> ParsingError {
+        #[cfg(feature = "trace")]
+        trace!(target: LOG_TARGET, "left recursion in rule '{rule}' at offset {offset}");
+        ParsingError::NoMatch {
+            action: "left recursion",
+            args: rule,
+        }
+    }
+
+    fn parse_detecting<'s>(
+        &self,
+        rule: &'static str,
+        s: &'s str,
+    ) -> Result<(&'s str, T), ParsingError> {
+        let key = (rule, self.offset(s));
+        if !self.active.borrow_mut().insert(key) {
+            let e = GrammarError::LeftRecursion {
+                rule,
+                offset: key.1,
+            };
+            self.fatal.borrow_mut().get_or_insert(e);
+            return Err(self.left_recursion_failure(rule, key.1));
+        }
+        let res = self.invoke(rule, s);
+        self.active.borrow_mut().remove(&key);
+        res
+    }
+
+    fn parse_memoised<'s>(
+        &self,
+        rule: &'static str,
+        s: &'s str,
+    ) -> Result<(&'s str, T), ParsingError> {
+        let key = (rule, self.offset(s));
+        match self.memo.borrow_mut().get_mut(&key) {
+            Some(Memo::Seed { left_recursive }) => {
+                *left_recursive = true;
+                if !self.left_recursion {
+                    let e = GrammarError::LeftRecursion {
+                        rule,
+                        offset: key.1,
+                    };
+                    self.fatal.borrow_mut().get_or_insert(e);
+                }
+                return Err(self.left_recursion_failure(rule, key.1));
+            }
+            Some(Memo::Done(res)) => {
+                self.stats.borrow_mut().entry(rule).or_default().hits += 1;
+                return res.clone().map(|(len, t)| (&s[len..], t));
+            }
+            None => {}
+        }
+        self.memo.borrow_mut().insert(
+            key,
+            Memo::Seed {
+                left_recursive: false,
+            },
+        );
+        let consumed = |r: &Result<(&'s str, T), ParsingError>| {
+            r.as_ref().map(|(rest, _)| s.len() - rest.len()).ok()
+        };
+        let mut res = self.invoke(rule, s);
+        let left_recursive = matches!(
+            self.memo.borrow().get(&key),
+            Some(Memo::Seed {
+                left_recursive: true
+            })
+        );
+        if left_recursive && self.left_recursion {
+            // grow the seed: each iteration sees the previous best result when it recurses.
+            // Results of other rules at this offset may depend on the seed, so are discarded
+            loop {
+                let best = consumed(&res);
+                self.memo
+                    .borrow_mut()
+                    .retain(|k, m| k.1 != key.1 || matches!(m, Memo::Seed { .. }));
+                self.memo
+                    .borrow_mut()
+                    .insert(key, Memo::Done(memo_entry(s, &res)));
+                let next = self.invoke(rule, s);
+                match (consumed(&next), best) {
+                    (Some(n), Some(b)) if n > b => res = next,
+                    (Some(_), None) => res = next,
+                    _ => break,
+                }
+            }
+        }
+        // a clone would lose the source of an error, so such a rule is run again instead
+        match &res {
+            Err(e) if e.source().is_some() => self.memo.borrow_mut().remove(&key),
+            _ => self
+                .memo
+                .borrow_mut()
+                .insert(key, Memo::Done(memo_entry(s, &res))),
+        };
+        res
+    }
+}
+
+/// invocation counts for a single rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuleStats {
+    pub calls: usize,
+    /// calls answered from the memo table, without running the rule
+    pub hits: usize,
+}
+
+impl RuleStats {
+    pub fn hit_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.calls as f64
+        }
+    }
+}
+
+/// per-rule statistics, which display as a table
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoStats {
+    pub rules: Vec<(&'static str, RuleStats)>,
+}
+
+impl MemoStats {
+    pub fn total(&self) -> RuleStats {
+        self.rules
+            .iter()
+            .fold(RuleStats::default(), |acc, (_, s)| RuleStats {
+                calls: acc.calls + s.calls,
+                hits: acc.hits + s.hits,
+            })
+    }
+}
+
+impl fmt::Display for MemoStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<20} {:>10} {:>10} {:>8}",
+            "rule", "calls", "hits", "hit %"
+        )?;
+        let total = self.total();
+        for (rule, s) in self.rules.iter().chain([&("(total)", total)]) {
+            writeln!(
+                f,
+                "{rule:<20} {calls:>10} {hits:>10} {rate:>8.1}",
+                calls = s.calls,
+                hits = s.hits,
+                rate = 100.0 * s.hit_rate()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn memo_entry<T: Clone>(
+    s: &str,
+    res: &Result<(&str, T), ParsingError>,
+) -> Result<(usize, T), ParsingError> {
+    res.as_ref()
+        .map(|(rest, t)| (s.len() - rest.len(), t.clone()))
+        .map_err(|e| e.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Recoverable;
+    use test_log::test;
+
+    fn num<'s>(_g: &Grammar<String>, s: &'s str) -> Result<(&'s str, String), ParsingError> {
+        Cursor::from(s)
+            .digits(1..)
+            .parse_selection::<String>()
+            .validate()
+    }
+
+    // sum := sum '+' term | term
+    // term := term '*' num | num
+    fn arithmetic<'g>() -> Grammar<'g, String> {
+        Grammar::new()
+            .rule("sum", |g, s| {
+                if let Ok((c, a, b)) = Cursor::from(s)
+                    .parse_with(|s| g.parse("sum", s))
+                    .text("+")
+                    .parse_with(|s| g.parse("term", s))
+                    .validate()
+                {
+                    return Ok((c, format!("({a}+{b})")));
+                }
+                g.parse("term", s)
+            })
+            .rule("term", |g, s| {
+                if let Ok((c, a, b)) = Cursor::from(s)
+                    .parse_with(|s| g.parse("term", s))
+                    .text("*")
+                    .parse_with(|s| g.parse("num", s))
+                    .validate()
+                {
+                    return Ok((c, format!("({a}*{b})")));
+                }
+                g.parse("num", s)
+            })
+            .rule("num", num)
+    }
+
+    #[test]
+    fn test_left_recursion_detected() {
+        let g = arithmetic();
+        let e = g.parse("sum", "1+2").unwrap_err();
+        assert_eq!(e.is_recoverable(), false);
+        assert_eq!(
+            e.to_string(),
+            "Fatal:left recursion in rule 'sum' at offset 0 (see Grammar::allow_left_recursion)"
+        );
+
+        // non left-recursive rules are fine
+        assert_eq!(g.parse("num", "12+3").unwrap(), ("+3", "12".to_string()));
+    }
+
+    #[test]
+    fn test_left_recursion_grown() {
+        let g = arithmetic().allow_left_recursion();
+        assert_eq!(g.parse("sum", "1").unwrap().1, "1");
+        assert_eq!(g.parse("sum", "1+2+3").unwrap().1, "((1+2)+3)");
+        assert_eq!(g.parse("sum", "1+2*3*4+5").unwrap().1, "((1+((2*3)*4))+5)");
+        assert_eq!(
+            g.parse("sum", "1+2*X").unwrap(),
+            ("*X", "(1+2)".to_string())
+        );
+        assert_eq!(g.parse("sum", "X").is_err(), true);
+    }
+
+    #[test]
+    fn test_indirect_left_recursion() {
+        // a := b 'x' | 'y'
+        // b := a
+        let g = Grammar::new()
+            .rule("a", |g, s| {
+                if let Ok((c, b)) = Cursor::from(s)
+                    .parse_with(|s| g.parse("b", s))
+                    .text("x")
+                    .validate()
+                {
+                    return Ok((c, b + "x"));
+                }
+                Cursor::from(s)
+                    .text("y")
+                    .validate()
+                    .map(|c| (c, "y".to_string()))
+            })
+            .rule("b", |g, s| g.parse("a", s));
+        assert_eq!(g.parse("a", "yxx").is_err(), true);
+
+        let g = g.allow_left_recursion();
+        assert_eq!(g.parse("a", "yxx!").unwrap(), ("!", "yxx".to_string()));
+    }
+
+    #[test]
+    fn test_recursive_rules() {
+        // list := '[' (list | num)* ']'
+        let g = Grammar::new()
+            .rule("list", |g, s| {
+                let (c, items) = Cursor::from(s)
+                    .text("[")
+                    .parse_struct_vec(|s| {
+                        Cursor::from(s)
+                            .parse_with(|s| g.parse("item", s))
+                            .maybe(",")
+                            .validate()
+                    })
+                    .text("]")
+                    .validate()?;
+                Ok((c, format!("<{}>", items.join(" "))))
+            })
+            .rule("item", |g, s| {
+                g.parse("list", s).or_else(|_| g.parse("num", s))
+            })
+            .rule("num", num);
+        assert_eq!(
+            g.parse("list", "[1,[2,3],[[]]]").unwrap().1,
+            "<1 <2 3> <<>>>"
+        );
+
+        let g = Grammar::<String>::new().rule("a", |g, s| g.parse("b", s));
+        assert_eq!(
+            g.parse("a", "").unwrap_err().to_string(),
+            "Fatal:unknown rule 'b'"
+        );
+    }
+
+    // value := pair '!' | pair '?' | pair
+    // pair  := '(' value ',' value ')' | digits
+    // exponential in nesting depth without memoisation
+    fn backtracking<'g>() -> Grammar<'g, String> {
+        Grammar::new()
+            .rule("value", |g, s| {
+                for suffix in ["!", "?"] {
+                    if let Ok((c, p)) = Cursor::from(s)
+                        .parse_with(|s| g.parse("pair", s))
+                        .text(suffix)
+                        .validate()
+                    {
+                        return Ok((c, p + suffix));
+                    }
+                }
+                g.parse("pair", s)
+            })
+            .rule("pair", |g, s| {
+                if let Ok((c, a, b)) = Cursor::from(s)
+                    .text("(")
+                    .parse_with(|s| g.parse("value", s))
+                    .text(",")
+                    .parse_with(|s| g.parse("value", s))
+                    .text(")")
+                    .validate()
+                {
+                    return Ok((c, format!("<{a} {b}>")));
+                }
+                g.parse("num", s)
+            })
+            .rule("num", num)
+    }
+
+    #[test]
+    fn test_packrat() {
+        let input = "((((1,2),3),4),5)";
+
+        let g = backtracking();
+        let (_, plain) = g.parse("value", input).unwrap();
+        let plain_calls = g.stats().total().calls;
+        assert_eq!(g.stats().total().hits, 0);
+
+        let g = backtracking().memoise();
+        let (_, memoised) = g.parse("value", input).unwrap();
+        assert_eq!(memoised, plain);
+        assert_eq!(memoised, "<<<<1 2> 3> 4> 5>");
+        let stats = g.stats();
+        assert!(stats.total().calls < plain_calls / 4, "{stats}");
+        assert!(stats.total().hits > 0);
+
+        g.reset_stats();
+        assert_eq!(g.stats().total(), RuleStats::default());
+
+        // memoisation alone doesn't permit left recursion
+        let g = arithmetic().memoise();
+        assert_eq!(g.parse("sum", "1+2").unwrap_err().is_recoverable(), false);
+    }
+
+    #[test]
+    fn test_memo_keeps_error_source() {
+        let sources = RefCell::new(vec![]);
+        let g = Grammar::new()
+            .memoise()
+            .rule("byte", |_g, s| {
+                Cursor::from(s)
+                    .digits(1..)
+                    .parse_selection_keep_source::<u8>()
+                    .validate()
+            })
+            .rule("twice", |g, s| {
+                for _ in 0..2 {
+                    let e = g.parse("byte", s).unwrap_err();
+                    sources.borrow_mut().push(e.source().map(|e| e.to_string()));
+                }
+                g.parse("byte", s)
+            });
+        let e = g.parse("twice", "300").unwrap_err();
+        assert_eq!(e.source().is_some(), true);
+        let source = Some("number too large to fit in target type".to_string());
+        assert_eq!(*sources.borrow(), [source.clone(), source]);
+
+        // errors without a source are still answered from the memo
+        g.reset_stats();
+        assert_eq!(g.parse("twice", "x").is_err(), true);
+        assert_eq!(g.stats().total().hits, 2);
+    }
+}
@@ -0,0 +1,155 @@
+use std::{fmt, ops::Range};
+
+/// A range of byte offsets into the original input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+impl From<Span> for Range<usize> {
+    fn from(span: Span) -> Self {
+        span.range()
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// A parsed value along with the span of input it was parsed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(value: T, span: Span) -> Self {
+        Self { value, span }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Spanned<U> {
+        Spanned {
+            value: f(self.value),
+            span: self.span,
+        }
+    }
+}
+
+/// The address range of the original input, from which offsets are calculated.
+///
+/// Stir-style parsers are handed a `&str` suffix rather than the input as a whole,
+/// so the origin is passed to nested parsers via the parse state (see `limits`).
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Origin {
+    start: usize,
+    end: usize,
+}
+
+impl Origin {
+    pub(crate) const NONE: Origin = Origin { start: 0, end: 0 };
+
+    pub(crate) fn of(s: &str) -> Self {
+        let start = s.as_ptr() as usize;
+        Self {
+            start,
+            end: start + s.len(),
+        }
+    }
+
+    pub(crate) fn contains(&self, s: &str) -> bool {
+        let start = s.as_ptr() as usize;
+        self.start <= start && start + s.len() <= self.end && self.end > 0
+    }
+
+    pub(crate) fn offset(&self, s: &str) -> usize {
+        (s.as_ptr() as usize).saturating_sub(self.start)
+    }
+
+    pub(crate) fn span(&self, s: &str) -> Span {
+        let start = self.offset(s);
+        Span::new(start, start + s.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use test_log::test;
+
+    #[derive(Debug, PartialEq)]
+    struct KeyValue {
+        key: Spanned<String>,
+        value: Spanned<i32>,
+    }
+
+    fn key_value(s: &str) -> Result<(&str, KeyValue), ParsingError> {
+        let (c, key, value) = Cursor::from(s)
+            .ws()
+            .alphabetics(1..)
+            .parse_selection_spanned()
+            .text("=")
+            .digits(1..)
+            .parse_selection_spanned()
+            .validate()?;
+        Ok((c, KeyValue { key, value }))
+    }
+
+    #[test]
+    fn test_parse_selection_spanned() {
+        let (_c, kv) = key_value("  abc=42").unwrap();
+        assert_eq!(kv.key, Spanned::new("abc".to_string(), Span::new(2, 5)));
+        assert_eq!(kv.value.value, 42);
+        assert_eq!(kv.value.span.range(), 6..8);
+        assert_eq!(&"  abc=42"[kv.value.span.range()], "42");
+    }
+
+    #[test]
+    fn test_nested_spans() {
+        // spans of nested stir-style parsers are relative to the outermost input
+        let input = "{a=1, bb=22, ccc=333}";
+        let (c, vec) = Cursor::from(input)
+            .text("{")
+            .parse_struct_vec(|s| Cursor::from(s).parse_with(key_value).maybe(",").validate())
+            .text("}")
+            .validate()
+            .unwrap();
+        assert_eq!(c, "");
+        let spans: Vec<_> = vec.iter().map(|kv| kv.value.span.to_string()).collect();
+        assert_eq!(spans, ["3..4", "9..11", "17..20"]);
+
+        let (_c, kv) = Cursor::from(input)
+            .find("bb")
+            .parse_spanned(key_value)
+            .validate()
+            .unwrap();
+        assert_eq!(kv.span, Span::new(6, 11));
+        assert_eq!(kv.value.key.span, Span::new(6, 8));
+
+        // called directly, the input is the origin
+        let (_c, kv) = key_value(&input[6..]).unwrap();
+        assert_eq!(kv.key.span, Span::new(0, 2));
+    }
+}
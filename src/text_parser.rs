@@ -4,15 +4,15 @@ use std::{
     str::FromStr,
 };
 
-use log::log_enabled;
-use log::Level::Trace;
-
 use crate::{
-    cursor::Selection,
-    error,
+    context,
+    cursor::{Captures, Selection},
+    error::{self, Recoverable},
+    limits,
     logging::Loggable,
-    prelude::{Cursor, ParsingError},
-    LABEL, LOG_TARGET,
+    prelude::{Cursor, ParsingError, Span, Spanned},
+    span::Origin,
+    tracer::{self, EventKind},
 };
 
 fn cursorify<'a, T>(
@@ -74,6 +74,104 @@ where
     }
 }
 
+/// Transforms or checks the value captured by the most recent `parse_*` call, without
+/// leaving the chain. A rejected value fails the cursor with `ParsingError::Invalid`
+pub trait MapLast<'a, T>: Sized {
+    type Output<U>;
+
+    fn map_last<U, F: FnOnce(T) -> U>(self, f: F) -> Self::Output<U> {
+        self.try_map_last(|t| Ok::<_, ParsingError>(f(t)))
+    }
+
+    fn try_map_last<U, E, F>(self, f: F) -> Self::Output<U>
+    where
+        E: Into<Box<dyn std::error::Error>>,
+        F: FnOnce(T) -> Result<U, E>;
+
+    fn verify_last<F: FnOnce(&T) -> bool>(self, f: F) -> Self;
+}
+
+impl<'a, C, T> MapLast<'a, T> for (C, Option<T>)
+where
+    C: Matchable<'a>,
+    T: Debug,
+{
+    type Output<U> = (C, Option<U>);
+
+    fn try_map_last<U, E, F>(self, f: F) -> Self::Output<U>
+    where
+        E: Into<Box<dyn std::error::Error>>,
+        F: FnOnce(T) -> Result<U, E>,
+    {
+        let (c, opt_t) = self;
+        let Some(t) = opt_t else {
+            return (c, None);
+        };
+        c.log_inputs("try_map_last", &t);
+        match f(t) {
+            Ok(u) => (c, Some(u)),
+            Err(e) => {
+                let e = invalid(&c, "try_map_last", Some(e.into()));
+                c.log_failure("try_map_last", "", &e);
+                (c.set_error(e), None)
+            }
+        }
+    }
+
+    fn verify_last<F: FnOnce(&T) -> bool>(self, f: F) -> Self {
+        let (c, opt_t) = self;
+        match opt_t {
+            Some(t) if !f(&t) => {
+                let e = invalid(&c, "verify_last", None);
+                c.log_failure("verify_last", &t, &e);
+                (c.set_error(e), None)
+            }
+            opt_t => (c, opt_t),
+        }
+    }
+}
+
+fn invalid<'a, C: Matchable<'a>>(
+    c: &C,
+    action: &'static str,
+    source: Option<Box<dyn std::error::Error>>,
+) -> ParsingError {
+    // positioned at the value, where its selection is known, rather than after it
+    let offset = match c.selection_span() {
+        Some(span) => span.start,
+        None => offset_of(c),
+    };
+    ParsingError::Invalid {
+        action,
+        offset,
+        context: context::context_path(),
+        source,
+    }
+}
+
+fn offset_of<'a, C: Matchable<'a>>(c: &C) -> usize {
+    let s = c.str().unwrap_or_default();
+    c.origin().unwrap_or_else(|| Origin::of(s)).offset(s)
+}
+
+/// Integer types that can be parsed from text in a given radix, as used by
+/// `parse_selection_radix`
+pub trait FromStrRadix: Sized {
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, std::num::ParseIntError>;
+}
+
+macro_rules! impl_from_str_radix {
+    ($($t:ty)*) => {$(
+        impl FromStrRadix for $t {
+            fn from_str_radix(s: &str, radix: u32) -> Result<Self, std::num::ParseIntError> {
+                <$t>::from_str_radix(s, radix)
+            }
+        }
+    )*}
+}
+
+impl_from_str_radix!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
 fn start_end<R: RangeBounds<i32>>(rb: &R) -> (Option<i32>, Option<i32>) {
     let start = match rb.start_bound() {
         Bound::Included(&i) => Some(i),
@@ -106,6 +204,10 @@ where
     let Ok(s) = cur.str() else {
         return cur;
     };
+    if let Err(e) = limits::step(s) {
+        cur.log_failure(action, args, &e);
+        return cur.set_error(e);
+    }
     let (start, end) = start_end(rb);
     if let Some(end) = end {
         if end < 0 {
@@ -118,36 +220,22 @@ where
     let start = start.unwrap_or_default() as usize;
     let end = end.unwrap_or(i32::MAX) as usize;
 
-    if let Some((i, _t)) = s.match_indices(pred).next() {
-        if i >= start && i <= end + 1 {
-            let cur = cur.set_str(&s[i..]);
-            cur.log_success(action, args);
-            return cur;
-        }
-    } else if rb.contains(&0) {
-        // if 0 in range we can always return where we are
-        return cur.set_str(s);
-    } else {
-        let len = s.chars().count();
-        if len < start {
-            let e = ParsingError::NoMatch {
-                action,
-                args: "len>start",
-            };
-            cur.log_failure(action, args, &e);
-            return cur.set_error(e);
-        } else if len > end {
-            let (i, _c) = s.char_indices().nth(end).unwrap();
-            let cur = cur.set_str(&s[i..]);
-            cur.log_success(action, args);
-            return cur;
-        } else if len == end || start_end(rb).1.is_none() {
-            let cur = cur.set_str("");
-            cur.log_success(action, args);
-            return cur;
+    // the chars matched, up to the maximum
+    let mut pred = pred;
+    let mut count = 0;
+    let mut i = s.len();
+    for (j, c) in s.char_indices() {
+        if count == end || pred(c) {
+            i = j;
+            break;
         }
+        count += 1;
+    }
+    if count >= start {
+        let cur = cur.set_str(&s[i..]);
+        cur.log_success(action, args);
+        return cur;
     }
-    // not found and len < end
     let e = ParsingError::NoMatch {
         action,
         args: "no match",
@@ -157,26 +245,30 @@ where
 }
 
 #[inline]
-fn apply<'a, C, F>(cur: C, f: F, msg: &'static str, args: &str) -> C
+pub(crate) fn apply<'a, C, F>(cur: C, f: F, msg: &'static str, args: &str) -> C
 where
     C: Matchable<'a>,
     F: FnOnce(&str) -> Option<&str>,
 {
     cur.log_inputs(msg, args);
-    match cur.str() {
-        Ok(s) => match f(s) {
-            Some(s) => {
-                let cur = cur.set_str(s);
-                cur.log_success(msg, args);
-                cur
-            }
-            None => {
-                let e = error::failure(msg, s);
-                cur.log_failure(msg, args, &e);
-                cur.set_error(e)
-            }
-        },
-        _ => cur,
+    let Ok(s) = cur.str() else {
+        return cur;
+    };
+    if let Err(e) = limits::step(s) {
+        cur.log_failure(msg, args, &e);
+        return cur.set_error(e);
+    }
+    match f(s) {
+        Some(s) => {
+            let cur = cur.set_str(s);
+            cur.log_success(msg, args);
+            cur
+        }
+        None => {
+            let e = error::failure(msg, s);
+            cur.log_failure(msg, args, &e);
+            cur.set_error(e)
+        }
     }
 }
 
@@ -188,6 +280,72 @@ pub trait Selectable<'a>: Matchable<'a> {
     fn selection_end(self) -> Self;
     fn selection_start(self) -> Self;
 
+    /// the named selection registers. The default keeps none, so is always empty
+    fn captures(&self) -> &Captures<'a> {
+        &crate::cursor::NO_CAPTURES
+    }
+
+    /// store text in a named selection register. The default discards it
+    fn capture(self, _name: &'static str, _text: &'a str) -> Self {
+        self
+    }
+
+    /// copies the current selection into a named register
+    fn selection_into(self, name: &'static str) -> Self {
+        if self.is_skip() {
+            return self;
+        }
+        match self.get_selection() {
+            Ok(text) => {
+                self.log_success("selection_into", name);
+                self.capture(name, text)
+            }
+            Err(e) => self.set_error(e),
+        }
+    }
+
+    /// as `select`, with the selection also stored in a named register
+    fn select_into<P>(self, name: &'static str, parser: P) -> Self
+    where
+        P: FnMut(Self) -> Self,
+    {
+        self.select(parser).selection_into(name)
+    }
+
+    fn get_selection_named(&self, name: &'static str) -> Result<&'a str, ParsingError> {
+        self.captures().get(name).ok_or(ParsingError::NoMatch {
+            action: "get_selection_named",
+            args: name,
+        })
+    }
+
+    /// as `parse_selection`, but parsing the text of a named register
+    fn parse_selection_named<T: FromStr + Debug>(self, name: &'static str) -> (Self, Option<T>) {
+        if self.is_skip() {
+            return (self, None);
+        }
+        let parsed = self.get_selection_named(name).and_then(|text| {
+            text.parse::<T>()
+                .map_err(|_| ParsingError::conversion_of::<T>(text))
+        });
+        match parsed {
+            Ok(t) => {
+                self.log_success_with_result("parse_selection_named", name, &t);
+                (self, Some(t))
+            }
+            Err(e) => {
+                self.log_failure("parse_selection_named", name, &e);
+                (self.set_error(e), None)
+            }
+        }
+    }
+
+    /// as `validate`, additionally returning the named selection registers
+    fn validate_captures(self) -> Result<(Self::DeTuple, Captures<'a>), ParsingError> {
+        let captures = self.captures().clone();
+        Ok((self.validate()?, captures))
+    }
+
     // fn de_nest_tuple<S, T, U>(((s, t), u): ((S, T), U)) -> (S, T, U) {
     //     (s, t, u)
     // }
@@ -235,17 +393,14 @@ pub trait Selectable<'a>: Matchable<'a> {
                 return match text.parse::<T>() {
                     Ok(t) => {
                         self.log_success_with_result(
-                            "----> get_selection",
+                            "parse_selection",
                             std::any::type_name::<T>(),
                             &t,
                         );
                         (self, Some(t))
                     }
-                    Err(..) => {
-                        let e = ParsingError::NoMatch {
-                            action: "FromStr",
-                            args: "",
-                        };
+                    Err(_) => {
+                        let e = ParsingError::conversion_of::<T>(text);
                         self.log_failure("parse_selection", "", &e);
                         (self.set_error(e), None)
                     }
@@ -255,6 +410,32 @@ pub trait Selectable<'a>: Matchable<'a> {
         (self, None)
     }
 
+    /// as `parse_selection`, but keeping the `FromStr` error as the source of the
+    /// `ParsingError::Conversion`, which needs the error type to implement `Error`
+    fn parse_selection_keep_source<T: FromStr + Debug>(self) -> (Self, Option<T>)
+    where
+        T::Err: Into<Box<dyn std::error::Error>>,
+    {
+        self.parse_selection_with(|text| {
+            text.parse::<T>()
+                .map_err(|e| ParsingError::conversion::<T>(text, e))
+        })
+    }
+
+    /// byte offsets of the selection within the original input
+    fn get_selection_span(&self) -> Result<Span, ParsingError> {
+        let text = self.get_selection()?;
+        Ok(self.origin().unwrap_or_else(|| Origin::of(text)).span(text))
+    }
+
+    /// as `parse_selection`, but also capturing where in the input the value came from
+    fn parse_selection_spanned<T: FromStr + Debug>(self) -> (Self, Option<Spanned<T>>) {
+        let span = self.get_selection_span().ok();
+        let (cur, opt_t) = self.parse_selection::<T>();
+        let spanned = opt_t.zip(span).map(|(t, span)| Spanned::new(t, span));
+        (cur, spanned)
+    }
+
     fn parse_opt_selection<T: FromStr + Debug>(self) -> (Self, Option<Option<T>>) {
         self.log_inputs("parse_selection", std::any::type_name::<T>());
         if let Ok(text) = self.get_selection() {
@@ -262,7 +443,7 @@ pub trait Selectable<'a>: Matchable<'a> {
                 return match text.parse::<T>() {
                     Ok(t) => {
                         self.log_success_with_result(
-                            "----> get_opt_selection",
+                            "parse_opt_selection",
                             std::any::type_name::<T>(),
                             &t,
                         );
@@ -270,7 +451,7 @@ pub trait Selectable<'a>: Matchable<'a> {
                     }
                     Err(..) => {
                         self.log_success_with_result(
-                            "----> get_opt_selection",
+                            "parse_opt_selection",
                             std::any::type_name::<T>(),
                             "None",
                         );
@@ -282,17 +463,65 @@ pub trait Selectable<'a>: Matchable<'a> {
         (self, None)
     }
 
+    /// as `parse_selection`, but assigning the value to a variable or struct field,
+    /// rather than adding it to the tuple returned by `validate`
+    fn parse_selection_into<T: FromStr + Debug>(self, target: &mut T) -> Self {
+        self.parse_selection::<T>().bind(target)
+    }
+
+    fn parse_selection_as_str_into(self, target: &mut &'a str) -> Self {
+        self.parse_selection_as_str().bind(target)
+    }
+
     fn parse_selection_as_str(self) -> (Self, Option<&'a str>) {
         self.log_inputs("parse_selection_as_str", "");
         if let Ok(text) = self.get_selection() {
             if let Ok(_cur) = self.str() {
-                self.log_success_with_result("----> parse_selection_as_str", "", text);
+                self.log_success_with_result("parse_selection_as_str", "", text);
                 return (self, Some(text));
             }
         }
         (self, None)
     }
 
+    /// as `parse_selection`, but converting the selected text with the supplied function
+    fn parse_selection_with<T, E, F>(self, f: F) -> (Self, Option<T>)
+    where
+        T: Debug,
+        E: Into<ParsingError>,
+        F: FnOnce(&'a str) -> Result<T, E>,
+    {
+        self.log_inputs("parse_selection_with", std::any::type_name::<T>());
+        let Ok(text) = self.get_selection() else {
+            return (self, None);
+        };
+        if self.str().is_err() {
+            return (self, None);
+        }
+        match f(text) {
+            Ok(t) => {
+                self.log_success_with_result(
+                    "parse_selection_with",
+                    std::any::type_name::<T>(),
+                    &t,
+                );
+                (self, Some(t))
+            }
+            Err(e) => {
+                let e = e.into();
+                self.log_failure("parse_selection_with", "", &e);
+                (self.set_error(e), None)
+            }
+        }
+    }
+
+    /// parses the selection as an integer in the given radix, eg 16 for hex digits
+    fn parse_selection_radix<T: FromStrRadix + Debug>(self, radix: u32) -> (Self, Option<T>) {
+        self.parse_selection_with(|text| {
+            T::from_str_radix(text, radix).map_err(|e| ParsingError::conversion::<T>(text, e))
+        })
+    }
+
     // fn parse_selection_as_f64(self) -> Result<Self::TupleReturn<f64>, ParseError> {
     //     let text = self.get_selection()?;
     //     let cur = self.str()?;
@@ -401,10 +630,12 @@ pub trait Matchable<'a>: Sized {
 
     #[inline]
     fn debug_context(self, span_name: &'static str) -> Self {
-        if log_enabled!(target: LOG_TARGET, Trace) {
-            LABEL.with(|f| f.set("")); // blank the span name before logging
-            self.log_success("debug_context", span_name);
-            LABEL.with(|f| f.set(span_name));
+        match self.context_base() {
+            Some(base) => context::set_at(base, span_name),
+            None => context::set(span_name),
+        }
+        if tracer::enabled() {
+            self.log_event(EventKind::Context, "debug_context", &span_name, None, None);
         }
         self
     }
@@ -412,6 +643,29 @@ pub trait Matchable<'a>: Sized {
     // fn validate(self) -> std::result::Result<Self, ParseError>;
     fn validate(self) -> std::result::Result<Self::DeTuple, ParsingError>;
 
+    /// the input from which offsets are calculated, if known
+    #[doc(hidden)]
+    fn origin(&self) -> Option<Origin> {
+        None
+    }
+
+    #[doc(hidden)]
+    fn selection_span(&self) -> Option<Span> {
+        None
+    }
+
+    /// the remaining input when the cursor failed, if known
+    #[doc(hidden)]
+    fn failed_at(&self) -> Option<&'a str> {
+        None
+    }
+
+    /// where the cursor's debug_context label goes on the context stack, if known
+    #[doc(hidden)]
+    fn context_base(&self) -> Option<usize> {
+        None
+    }
+
     fn is_skip(&self) -> bool {
         self.str().is_err()
     }
@@ -654,19 +908,23 @@ pub trait Matchable<'a>: Sized {
 
             return (self, None)
         };
+        let _depth = match limits::enter_from(self.origin(), str) {
+            Ok(depth) => depth,
+            Err(e) => return (self.set_error(e), None),
+        };
         loop {
             match (parser)(str) {
                 Ok((s, t)) => {
                     self.log_success_with_result(
-                        "----> parse_struct_vec",
+                        "parse_struct_vec",
                         std::any::type_name::<T>(),
                         &t,
                     );
                     vec.push(t);
                     str = s;
                 }
-                Err(ParsingError::NoMatch { .. }) => {
-                    self.log_success("----> parse_struct_vec, len", vec.len());
+                Err(e) if e.is_recoverable() => {
+                    self.log_success_with_result("parse_struct_vec", "len", vec.len());
                     return (self.set_str(str), Some(vec));
                 }
 
@@ -685,17 +943,15 @@ pub trait Matchable<'a>: Sized {
         // A: IntoIterator<Item = T>
     {
         let mut str = self.str()?;
+        let _depth = limits::enter_from(self.origin(), str)?;
         loop {
             match (parser)(str) {
                 Ok((s, t)) => {
                     vec.extend(std::iter::once(t));
                     str = s;
                 }
-                Err(ParsingError::NoMatch { .. }) => return Ok(self.set_str(str)),
-
-                Err(ParsingError::Fatal(e)) => {
-                    return Err(ParsingError::Fatal(e));
-                }
+                Err(e) if e.is_recoverable() => return Ok(self.set_str(str)),
+                Err(e) => return Err(e),
             }
         }
     }
@@ -755,6 +1011,11 @@ pub trait Matchable<'a>: Sized {
         // <Self as Matchable<'a>>::Cursor: 'a,
     {
         if !self.is_skip() {
+            let origin = self.origin();
+            let _depth = match limits::enter_from(origin, self.str().unwrap_or_default()) {
+                Ok(depth) => depth,
+                Err(e) => return (self.set_error(e), None),
+            };
             let res: Result<(C, T), ParsingError> = parser.parse(
                 self.cursor()
                     .clone()
@@ -787,6 +1048,11 @@ pub trait Matchable<'a>: Sized {
         // <Self as Matchable<'a>>::Cursor: 'a,
     {
         if !self.is_skip() {
+            let origin = self.origin();
+            let _depth = match limits::enter_from(origin, self.str().unwrap_or_default()) {
+                Ok(depth) => depth,
+                Err(e) => return (self.set_error(e), None),
+            };
             let res: Result<(C, T), ParsingError> = parser.parse(
                 self.cursor()
                     .clone()
@@ -810,6 +1076,39 @@ pub trait Matchable<'a>: Sized {
         (self, None)
     }
 
+    /// as `parse_with`, but assigning the parsed value to a variable or struct field
+    fn parse_with_into<P, C, T>(self, parser: P, target: &mut T) -> Self
+    where
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        self.parse_with(parser).bind(target)
+    }
+
+    /// as `parse_with`, but also capturing the span of input consumed by the parser
+    fn parse_spanned<P, C, T>(self, parser: P) -> (Self, Option<Spanned<T>>)
+    where
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        let Ok(start) = self.str() else {
+            return (self, None);
+        };
+        let origin = self.origin().unwrap_or_else(|| Origin::of(start));
+        let (cur, opt_t) = self.parse_with(parser);
+        match (cur.str(), opt_t) {
+            (Ok(end), Some(t)) => {
+                let span = origin.span(&start[..start.len() - end.len()]);
+                (cur, Some(Spanned::new(t, span)))
+            }
+            _ => (cur, None),
+        }
+    }
+
     // fn parse_with<P, C, T>(self, mut parser: P) -> (Self, Option<T>)
     // where
     //     P: FnMut(C) -> Result<(C, T), ParseError>,
@@ -961,6 +1260,15 @@ impl<'a> Matchable<'a> for Option<&'a str> {
 // }
 
 impl<'a> Selectable<'a> for Cursor<'a> {
+    fn captures(&self) -> &Captures<'a> {
+        &self.captures
+    }
+
+    fn capture(mut self, name: &'static str, text: &'a str) -> Self {
+        self.captures.insert(name, text);
+        self
+    }
+
     fn get_selection(&self) -> Result<&'a str, ParsingError> {
         self.log_inputs("get_selection", "");
         if let Some(cur) = self.cur {
@@ -980,6 +1288,10 @@ impl<'a> Selectable<'a> for Cursor<'a> {
                 selection: Selection::Start(cur, None),
                 err: self.err,
                 context: self.context,
+                origin: self.origin,
+                captures: self.captures,
+                failed_at: self.failed_at,
+                context_base: self.context_base,
             };
             cur.log_success("selection_end", "");
             cur
@@ -996,6 +1308,10 @@ impl<'a> Selectable<'a> for Cursor<'a> {
                 selection: Selection::Start(self.selection.start(), self.cur),
                 err: self.err,
                 context: self.context,
+                origin: self.origin,
+                captures: self.captures,
+                failed_at: self.failed_at,
+                context_base: self.context_base,
             };
             cur.log_success("selection_end", "");
             cur
@@ -1009,6 +1325,24 @@ impl<'a> Matchable<'a> for Cursor<'a> {
     type Cursor = Self;
     type DeTuple = &'a str;
 
+    fn origin(&self) -> Option<Origin> {
+        Some(self.origin)
+    }
+
+    fn selection_span(&self) -> Option<Span> {
+        let (s, e) = self.selection.selection(self.cur?);
+        let text = &s[..s.len().checked_sub(e.len())?];
+        Some(self.origin.span(text))
+    }
+
+    fn failed_at(&self) -> Option<&'a str> {
+        self.failed_at
+    }
+
+    fn context_base(&self) -> Option<usize> {
+        Some(self.context_base)
+    }
+
     #[inline]
     fn str(&self) -> Result<&'a str, ParsingError> {
         self.cur.str()
@@ -1024,7 +1358,11 @@ impl<'a> Matchable<'a> for Cursor<'a> {
             selection: self.selection.move_cursor(s),
             cur: self.cur.set_str(s),
             err: self.err,
+            failed_at: self.failed_at,
+            context_base: self.context_base,
             context: self.context,
+            origin: self.origin,
+            captures: self.captures,
         }
     }
 
@@ -1034,7 +1372,11 @@ impl<'a> Matchable<'a> for Cursor<'a> {
             selection: self.selection,
             cur: None,
             err: Some(e),
+            failed_at: self.cur.or(self.failed_at),
+            context_base: self.context_base,
             context: self.context,
+            origin: self.origin,
+            captures: self.captures,
         }
     }
 
@@ -1043,6 +1385,7 @@ impl<'a> Matchable<'a> for Cursor<'a> {
     // }
 
     fn validate(self) -> Result<Self::DeTuple, ParsingError> {
+        context::end(self.context_base);
         match self.err {
             None => Ok(self.str()?),
             Some(e) => Err(e),
@@ -1051,6 +1394,14 @@ impl<'a> Matchable<'a> for Cursor<'a> {
 }
 
 impl<'a, T> Selectable<'a> for (Cursor<'a>, Option<T>) {
+    fn captures(&self) -> &Captures<'a> {
+        self.0.captures()
+    }
+
+    fn capture(self, name: &'static str, text: &'a str) -> Self {
+        (self.0.capture(name, text), self.1)
+    }
+
     fn get_selection(&self) -> Result<&'a str, ParsingError> {
         self.0.get_selection()
     }
@@ -1077,6 +1428,22 @@ impl<'a, T> Matchable<'a> for (Cursor<'a>, Option<T>) {
         &self.0
     }
 
+    fn origin(&self) -> Option<Origin> {
+        self.0.origin()
+    }
+
+    fn selection_span(&self) -> Option<Span> {
+        self.0.selection_span()
+    }
+
+    fn failed_at(&self) -> Option<&'a str> {
+        self.0.failed_at()
+    }
+
+    fn context_base(&self) -> Option<usize> {
+        self.0.context_base()
+    }
+
     #[inline]
     fn set_str(self, s: &'a str) -> Self {
         (self.0.set_str(s), self.1)
@@ -1099,11 +1466,7 @@ impl<'a, T> Matchable<'a> for (Cursor<'a>, Option<T>) {
             args: "",
         };
         if self.0.err.is_none() {
-            self.log_success_with_result(
-                "----> validate",
-                std::any::type_name::<Self::DeTuple>(),
-                "",
-            );
+            self.log_success("validate", std::any::type_name::<Self::DeTuple>());
         }
 
         match self.0.validate() {
@@ -1126,6 +1489,22 @@ impl<'a, T1, T2> Matchable<'a> for ((Cursor<'a>, Option<T1>), Option<T2>) {
         &self.0 .0
     }
 
+    fn origin(&self) -> Option<Origin> {
+        self.0.origin()
+    }
+
+    fn selection_span(&self) -> Option<Span> {
+        self.0.selection_span()
+    }
+
+    fn failed_at(&self) -> Option<&'a str> {
+        self.0.failed_at()
+    }
+
+    fn context_base(&self) -> Option<usize> {
+        self.0.context_base()
+    }
+
     #[inline]
     fn set_str(self, s: &'a str) -> Self {
         (self.0.set_str(s), self.1)
@@ -1165,6 +1544,22 @@ impl<'a, T1, T2, T3> Matchable<'a> for (((Cursor<'a>, Option<T1>), Option<T2>),
         &self.0 .0 .0
     }
 
+    fn origin(&self) -> Option<Origin> {
+        self.0.origin()
+    }
+
+    fn selection_span(&self) -> Option<Span> {
+        self.0.selection_span()
+    }
+
+    fn failed_at(&self) -> Option<&'a str> {
+        self.0.failed_at()
+    }
+
+    fn context_base(&self) -> Option<usize> {
+        self.0.context_base()
+    }
+
     #[inline]
     fn set_str(self, s: &'a str) -> Self {
         (self.0.set_str(s), self.1)
@@ -1192,6 +1587,14 @@ impl<'a, T1, T2, T3> Matchable<'a> for (((Cursor<'a>, Option<T1>), Option<T2>),
 }
 
 impl<'a, T1, T2> Selectable<'a> for ((Cursor<'a>, Option<T1>), Option<T2>) {
+    fn captures(&self) -> &Captures<'a> {
+        self.0.captures()
+    }
+
+    fn capture(self, name: &'static str, text: &'a str) -> Self {
+        (self.0.capture(name, text), self.1)
+    }
+
     fn get_selection(&self) -> Result<&'a str, ParsingError> {
         self.0.get_selection()
     }
@@ -1206,6 +1609,14 @@ impl<'a, T1, T2> Selectable<'a> for ((Cursor<'a>, Option<T1>), Option<T2>) {
 }
 
 impl<'a, T1, T2, T3> Selectable<'a> for (((Cursor<'a>, Option<T1>), Option<T2>), Option<T3>) {
+    fn captures(&self) -> &Captures<'a> {
+        self.0.captures()
+    }
+
+    fn capture(self, name: &'static str, text: &'a str) -> Self {
+        (self.0.capture(name, text), self.1)
+    }
+
     fn get_selection(&self) -> Result<&'a str, ParsingError> {
         self.0.get_selection()
     }
@@ -1224,7 +1635,9 @@ mod tests {
 
     use std::ops::RangeBounds;
 
-    use crate::text_parser::{Bind, ParsingError, Selectable};
+    use crate::error::Recoverable;
+    use crate::text_parser::{Bind, MapLast, ParsingError, Selectable};
+    use std::error::Error;
 
     use super::{Cursor, Matchable};
     use test_log::test;
@@ -1352,6 +1765,262 @@ mod tests {
         assert_eq!(c, "");
     }
 
+    #[test]
+    fn test_map_last() {
+        let (c, hours, mins) = Cursor::from("0x17:59")
+            .text("0x")
+            .digits(2..=2)
+            .parse_selection_as_str()
+            .try_map_last(|s| u8::from_str_radix(s, 16))
+            .verify_last(|&h| h < 24)
+            .text(":")
+            .digits(2..=2)
+            .parse_selection::<u8>()
+            .map_last(|m| m as u32 * 60)
+            .validate()
+            .unwrap();
+        assert_eq!((c, hours, mins), ("", 23, 3540));
+
+        // the failure is positioned at the start of the rejected value
+        let res = Cursor::from("time=25:00")
+            .text("time=")
+            .digits(2..=2)
+            .parse_selection::<u8>()
+            .verify_last(|&h| h < 24)
+            .text(":")
+            .validate();
+        match res {
+            Err(ParsingError::Invalid { action, offset, .. }) => {
+                assert_eq!((action, offset), ("verify_last", 5))
+            }
+            r => panic!("unexpected {r:?}"),
+        }
+
+        let res = Cursor::from("0xZZ")
+            .text("0x")
+            .chars_any(2..=2)
+            .parse_selection_as_str()
+            .try_map_last(|s| u8::from_str_radix(s, 16))
+            .validate();
+        let e = res.unwrap_err();
+        assert_eq!(e.is_recoverable(), true);
+        assert_eq!(
+            e.to_string(),
+            "Invalid: (action='try_map_last' offset=2) invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_conversion_errors() {
+        let e = Cursor::from("300")
+            .digits(1..)
+            .parse_selection_keep_source::<u8>()
+            .validate()
+            .unwrap_err();
+        assert_eq!(e.is_recoverable(), true);
+        match &e {
+            ParsingError::Conversion {
+                text, type_name, ..
+            } => assert_eq!((text.as_deref(), *type_name), (Some("300"), "u8")),
+            e => panic!("unexpected {e:?}"),
+        }
+        let kind = |e: &ParsingError| {
+            e.source()
+                .and_then(|s| s.downcast_ref::<std::num::ParseIntError>())
+                .map(|pie| *pie.kind())
+        };
+        assert_eq!(kind(&e), Some(std::num::IntErrorKind::PosOverflow));
+
+        let e = Cursor::from("yes")
+            .alphabetics(1..)
+            .parse_selection::<bool>()
+            .validate()
+            .unwrap_err();
+        assert_eq!(e.to_string(), "Conversion: (text='yes' type='bool')");
+        assert_eq!(e.source().is_none(), true);
+
+        // an error type that is not an Error can still be used with parse_selection
+        #[derive(Debug)]
+        struct Yes;
+        impl std::str::FromStr for Yes {
+            type Err = ();
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                (s == "yes").then_some(Yes).ok_or(())
+            }
+        }
+        let res = Cursor::from("no").alphabetics(1..).parse_selection::<Yes>().validate();
+        assert!(matches!(res, Err(ParsingError::Conversion { text: Some(t), .. }) if t == "no"));
+
+        let e: ParsingError = "-".parse::<i32>().unwrap_err().into();
+        assert_eq!(kind(&e), Some(std::num::IntErrorKind::InvalidDigit));
+        assert_eq!(e.to_string(), "Conversion: (type='integer') invalid digit found in string");
+
+        // a value out of range ends a list rather than failing it
+        fn item(s: &str) -> Result<(&str, u8), ParsingError> {
+            Cursor::from(s).ws().digits(1..).parse_selection().validate()
+        }
+        let res = Cursor::from("1 2 300").parse_struct_vec(item).validate();
+        assert_eq!(res.unwrap(), (" 300", vec![1, 2]));
+        let mut vec = vec![];
+        let c = Cursor::from("1 2 300").parse_struct_vec_to(item, &mut vec).unwrap();
+        assert_eq!((c.str().unwrap(), vec), (" 300", vec![1, 2]));
+    }
+
+    #[test]
+    fn test_parse_selection_with() {
+        #[derive(Debug, PartialEq)]
+        enum Month {
+            Jan,
+            Feb,
+        }
+        let month = |s: &str| match s {
+            "Jan" => Ok(Month::Jan),
+            "Feb" => Ok(Month::Feb),
+            _ => Err(ParsingError::NoMatch {
+                action: "month",
+                args: "",
+            }),
+        };
+        let (c, m, d) = Cursor::from("Feb 29")
+            .alphabetics(3..=3)
+            .parse_selection_with(month)
+            .ws()
+            .digits(1..=2)
+            .parse_selection_with(|s| s.parse::<u8>())
+            .validate()
+            .unwrap();
+        assert_eq!((c, m, d), ("", Month::Feb, 29));
+
+        let res = Cursor::from("Mar 1")
+            .alphabetics(3..=3)
+            .parse_selection_with(month)
+            .validate();
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "FailedMatch: (action='month' args='')"
+        );
+    }
+
+    #[test]
+    fn test_parse_selection_radix() {
+        let (c, r, g, b) = Cursor::from("#FF8000")
+            .text("#")
+            .chars_any(2..=2)
+            .parse_selection_radix::<u8>(16)
+            .chars_any(2..=2)
+            .parse_selection_radix::<u8>(16)
+            .chars_any(2..=2)
+            .parse_selection_radix::<u8>(16)
+            .validate()
+            .unwrap();
+        assert_eq!((c, r, g, b), ("", 255, 128, 0));
+
+        let e = Cursor::from("0b102")
+            .text("0b")
+            .digits(1..)
+            .parse_selection_radix::<u32>(2)
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Conversion: (text='102' type='u32') invalid digit found in string"
+        );
+
+        let e = Cursor::from("1FF")
+            .chars_any(3..=3)
+            .parse_selection_radix::<u8>(16)
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            e.source().unwrap().to_string(),
+            "number too large to fit in target type"
+        );
+    }
+
+    #[test]
+    fn test_parse_into_fields() {
+        #[derive(Debug, Default, PartialEq)]
+        struct Reading<'a> {
+            hours: i32,
+            mins: i32,
+            unit: &'a str,
+            time: Option<Time>,
+        }
+        let mut r = Reading::default();
+        let c = Cursor::from("23:59 mph 01:02:03.456")
+            .digits(2..=2)
+            .parse_selection_into(&mut r.hours)
+            .text(":")
+            .digits(2..=2)
+            .parse_selection_into(&mut r.mins)
+            .ws()
+            .alphabetics(1..)
+            .parse_selection_as_str_into(&mut r.unit)
+            .ws()
+            .parse_with_into(|s| parse_time_v3(s).map(|(s, t)| (s, Some(t))), &mut r.time)
+            .validate()
+            .unwrap();
+        assert_eq!(c, "");
+        assert_eq!(
+            r,
+            Reading {
+                hours: 23,
+                mins: 59,
+                unit: "mph",
+                time: Some(Time(1, 2, 3.456))
+            }
+        );
+
+        let mut hours = 0;
+        let res = Cursor::from("XY")
+            .chars_any(2..=2)
+            .parse_selection_into(&mut hours)
+            .validate();
+        assert_eq!(res.is_err(), true);
+    }
+
+    #[test]
+    fn test_named_selections() {
+        let (c, caps) = Cursor::from("key = value; rest")
+            .select_into("key", |c| c.alphabetics(1..))
+            .ws()
+            .text("=")
+            .ws()
+            .selection_start()
+            .alphabetics(1..)
+            .selection_end()
+            .selection_into("value")
+            .text(";")
+            .validate_captures()
+            .unwrap();
+        assert_eq!(c, " rest");
+        assert_eq!(caps.get("key"), Some("key"));
+        assert_eq!(caps.get("value"), Some("value"));
+        assert_eq!(caps.nth(1), Some("value"));
+        assert_eq!(caps.get("missing"), None);
+
+        // registers survive later matching, and can be parsed after the event
+        let (c, mins, hours) = Cursor::from("12:34")
+            .select_into("hh", |c| c.digits(2..=2))
+            .text(":")
+            .select(|c| c.digits(2..=2))
+            .parse_selection::<u32>()
+            .parse_selection_named::<u32>("hh")
+            .validate()
+            .unwrap();
+        assert_eq!((c, hours, mins), ("", 12, 34));
+
+        let c = Cursor::from("ab").select_into("x", |c| c.text("a"));
+        assert_eq!(c.get_selection_named("x").unwrap(), "a");
+        assert_eq!(c.get_selection_named("y").is_err(), true);
+        let c = c.select_into("x", |c| c.text("b"));
+        assert_eq!(c.captures().iter().collect::<Vec<_>>(), [("x", "b")]);
+        assert_eq!(
+            c.parse_selection_named::<u32>("x").validate().is_err(),
+            true
+        );
+    }
+
     #[test]
     fn test_parse_range() {
         fn rb<R: RangeBounds<i32>>(_: R) {}
@@ -1360,6 +2029,32 @@ mod tests {
         rb(..);
     }
 
+    #[test]
+    fn test_bounded_ranges() {
+        let c = Cursor::from("12345").digits(1..=2);
+        assert_eq!(c.str().unwrap(), "345");
+        let c = Cursor::from("1a").digits(2..=2);
+        assert_eq!(c.str().is_err(), true);
+        let c = Cursor::from("ééx").chars_in(0..=1, &['é']);
+        assert_eq!(c.str().unwrap(), "éx");
+        let c = Cursor::from("123").digits(0..);
+        assert_eq!(c.str().unwrap(), "");
+        let c = Cursor::from("ééé").chars_in(3.., &['é']);
+        assert_eq!(c.str().unwrap(), "");
+
+        // the cases which misbehaved before, with more chars after the match
+        let c = Cursor::from("123").digits(..=2);
+        assert_eq!(c.str().unwrap(), "3");
+        let c = Cursor::from("12a").digits(1..=2);
+        assert_eq!(c.str().unwrap(), "a");
+        let c = Cursor::from("abcd1").alphabetics(2..=3);
+        assert_eq!(c.str().unwrap(), "d1");
+        let c = Cursor::from("ab").digits(0..=2);
+        assert_eq!(c.str().unwrap(), "ab");
+        let c = Cursor::from("a1").alphabetics(2..=3);
+        assert_eq!(c.str().is_err(), true);
+    }
+
     #[test]
     fn test_parse_nested() {
         fn rb<R: RangeBounds<i32>>(_: R) {}
@@ -0,0 +1,386 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug},
+    rc::Rc,
+};
+
+use log::Level::Trace;
+use log::{log_enabled, trace};
+
+use crate::{context::ContextPath, prelude::Span, util, LOG_TARGET};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    /// a grammar rule has been entered
+    Enter,
+    /// a grammar rule has completed, successfully or not
+    Exit,
+    /// `debug_context` has set a label, with `context` being the new path
+    Context,
+    /// a primitive (eg `digits`) is about to be attempted
+    Attempt,
+    /// a primitive was skipped, as the cursor had already failed
+    Skip,
+    Success,
+    Failure,
+}
+
+/// A trace event. Fields borrow from the parser, so are only formatted
+/// if a tracer chooses to do so.
+#[derive(Clone, Copy)]
+pub struct Event<'e> {
+    pub kind: EventKind,
+    /// the `debug_context` labels of the parsers in progress, eg "hash > key_value"
+    pub context: ContextPath,
+    /// the primitive or rule, eg "digits"
+    pub action: &'static str,
+    pub args: &'e dyn Debug,
+    /// the remaining input
+    pub input: &'e str,
+    /// the byte offset of `input` within the original input
+    pub offset: usize,
+    pub selection: Option<Span>,
+    /// the captured value (as Debug), for successful `parse_*` methods
+    pub value: Option<&'e dyn Debug>,
+    pub error: Option<&'e dyn Debug>,
+}
+
+impl<'e> Debug for Event<'e> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Event")
+            .field("kind", &self.kind)
+            .field("context", &self.context)
+            .field("action", &self.action)
+            .field("args", self.args)
+            .field("offset", &self.offset)
+            .field("selection", &self.selection)
+            .field("value", &self.value)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+/// Receives the structured trace events of parsing.
+///
+/// The default tracer writes events to the `log` crate (target "dc", level Trace).
+/// Others can be installed for the duration of a parse with [`with_tracer`].
+///
+/// Events are only generated with the `trace` cargo feature, which is off by default
+/// so that release builds carry no tracing code.
+pub trait Tracer {
+    /// whether events are wanted at all, checked before any event is built
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    fn event(&self, event: &Event);
+}
+
+thread_local!(static TRACER: RefCell<Option<Rc<dyn Tracer>>> = const { RefCell::new(None) });
+
+/// runs `f` with `tracer` receiving the trace events of any parsing on this thread
+pub fn with_tracer<R>(tracer: Rc<dyn Tracer>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Rc<dyn Tracer>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            TRACER.with(|t| *t.borrow_mut() = self.0.take());
+        }
+    }
+
+    let _restore = Restore(TRACER.with(|t| t.borrow_mut().replace(tracer)));
+    f()
+}
+
+/// calls `f` with the active tracer, if it is enabled
+#[cfg(any(test, feature = "trace"))]
+#[inline]
+pub(crate) fn dispatch(f: impl FnOnce(&dyn Tracer)) {
+    TRACER.with(|t| match &*t.borrow() {
+        Some(tracer) if tracer.enabled() => f(tracer.as_ref()),
+        Some(_) => {}
+        None if LogTracer.enabled() => f(&LogTracer),
+        None => {}
+    })
+}
+
+// without the `trace` feature, events are never built
+#[cfg(not(any(test, feature = "trace")))]
+#[inline(always)]
+pub(crate) fn dispatch(_f: impl FnOnce(&dyn Tracer)) {}
+
+#[cfg(any(test, feature = "trace"))]
+#[inline]
+pub(crate) fn enabled() -> bool {
+    let mut enabled = false;
+    dispatch(|_| enabled = true);
+    enabled
+}
+
+#[cfg(not(any(test, feature = "trace")))]
+#[inline(always)]
+pub(crate) fn enabled() -> bool {
+    false
+}
+
+pub(crate) fn rule_event(
+    kind: EventKind,
+    rule: &'static str,
+    s: &str,
+    offset: usize,
+    error: Option<&dyn Debug>,
+) {
+    dispatch(|t| {
+        t.event(&Event {
+            kind,
+            context: ContextPath::current(),
+            action: rule,
+            args: &"",
+            input: s,
+            offset,
+            selection: None,
+            value: None,
+            error,
+        })
+    })
+}
+
+/// Writes events as formatted lines to the `log` crate, which is the default
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogTracer;
+
+impl LogTracer {
+    const LABEL_WIDTH: usize = 15;
+    const INPUT_WIDTH: usize = 35;
+}
+
+impl Tracer for LogTracer {
+    fn enabled(&self) -> bool {
+        log_enabled!(target: LOG_TARGET, Trace)
+    }
+
+    fn event(&self, e: &Event) {
+        let context = e.context.to_string();
+        let (label, operation) = match e.kind {
+            EventKind::Attempt => return,
+            EventKind::Context => ("", format!("{}({:?})", e.action, e.args)),
+            EventKind::Enter => (context.as_str(), format!("enter {}", e.action)),
+            EventKind::Exit => (context.as_str(), format!("exit {}", e.action)),
+            EventKind::Success if e.value.is_some() => {
+                (context.as_str(), format!("----> {}:{:?}", e.action, e.args))
+            }
+            _ => (context.as_str(), format!("{}({:?})", e.action, e.args)),
+        };
+        let result = e.value.or(e.error).map(|r| format!(" -> {r:?}"));
+        trace!(
+            target: LOG_TARGET,
+            "{inp:<iw$} {label:<lw$} : {operation:<lw$}{res}",
+            iw = Self::INPUT_WIDTH,
+            lw = Self::LABEL_WIDTH,
+            inp = util::formatter_str(e.input),
+            res = result.unwrap_or_default(),
+        );
+    }
+}
+
+/// An owned copy of an [`Event`], as kept by a [`Recorder`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub kind: EventKind,
+    pub context: String,
+    pub action: &'static str,
+    pub args: String,
+    pub offset: usize,
+    pub selection: Option<Span>,
+    pub value: Option<String>,
+    pub error: Option<String>,
+}
+
+impl From<&Event<'_>> for Record {
+    fn from(e: &Event) -> Self {
+        Self {
+            kind: e.kind,
+            context: e.context.to_string(),
+            action: e.action,
+            args: format!("{:?}", e.args),
+            offset: e.offset,
+            selection: e.selection,
+            value: e.value.map(|v| format!("{v:?}")),
+            error: e.error.map(|e| format!("{e:?}")),
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{offset:>5} {kind:<8} {context}:{action}({args})",
+            offset = self.offset,
+            kind = format!("{:?}", self.kind),
+            context = self.context,
+            action = self.action,
+            args = self.args,
+        )?;
+        if let Some(v) = &self.value {
+            write!(f, " -> {v}")?;
+        }
+        if let Some(e) = &self.error {
+            write!(f, " -> {e}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Keeps the events in memory, for tests or later reporting
+#[derive(Debug, Default)]
+pub struct Recorder {
+    records: RefCell<Vec<Record>>,
+}
+
+impl Recorder {
+    pub fn new() -> Rc<Self> {
+        Rc::default()
+    }
+
+    pub fn records(&self) -> Vec<Record> {
+        self.records.borrow().clone()
+    }
+
+    pub fn clear(&self) {
+        self.records.borrow_mut().clear();
+    }
+}
+
+impl Tracer for Recorder {
+    fn event(&self, event: &Event) {
+        self.records.borrow_mut().push(event.into());
+    }
+}
+
+/// Forwards events to the `tracing` crate, with grammar rules as spans
+#[cfg(feature = "tracing")]
+#[derive(Debug, Default)]
+pub struct TracingTracer {
+    spans: RefCell<Vec<tracing::span::EnteredSpan>>,
+}
+
+#[cfg(feature = "tracing")]
+impl Tracer for TracingTracer {
+    fn enabled(&self) -> bool {
+        tracing::enabled!(target: "dc", tracing::Level::TRACE)
+    }
+
+    fn event(&self, e: &Event) {
+        match e.kind {
+            EventKind::Enter => {
+                let span =
+                    tracing::trace_span!(target: "dc", "rule", rule = e.action, offset = e.offset);
+                self.spans.borrow_mut().push(span.entered());
+            }
+            EventKind::Exit => {
+                self.spans.borrow_mut().pop();
+            }
+            kind => tracing::trace!(
+                target: "dc",
+                kind = ?kind,
+                context = %e.context,
+                action = e.action,
+                args = ?e.args,
+                offset = e.offset,
+                selection = ?e.selection,
+                value = ?e.value,
+                error = ?e.error,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::Grammar;
+    use crate::prelude::*;
+    use test_log::test;
+
+    #[test]
+    fn test_recorder() {
+        let rec = Recorder::new();
+        let res = with_tracer(rec.clone(), || {
+            Cursor::from("ab12")
+                .debug_context("pair")
+                .alphabetics(1..)
+                .digits(1..)
+                .parse_selection::<u32>()
+                .text("!")
+                .digits(1..)
+                .validate()
+        });
+        assert_eq!(res.is_err(), true);
+
+        let lines: Vec<_> = rec.records().iter().map(|r| r.to_string()).collect();
+        let expected = [
+            "    0 Success  :Cursor::from(\"\")",
+            "    0 Context  pair:debug_context(\"pair\")",
+            "    0 Attempt  pair:alphabetics(1..)",
+            "    2 Success  pair:alphabetics(1..)",
+            "    2 Attempt  pair:digits(1..)",
+            "    4 Success  pair:digits(1..)",
+            "    4 Attempt  pair:parse_selection(\"u32\")",
+            "    4 Attempt  pair:get_selection(\"\")",
+            "    4 Success  pair:get_selection(\"12\")",
+            "    4 Success  pair:parse_selection(\"u32\") -> 12",
+            "    4 Attempt  pair:text(\"!\")",
+            "    4 Failure  pair:text(\"!\") -> NoMatch { action: \"text\", args: \"\" }",
+            "    4 Skip     pair:digits(1..)",
+        ];
+        assert_eq!(lines, expected, "{lines:#?}");
+
+        let parsed = &rec.records()[9];
+        assert_eq!(parsed.selection, Some(Span::new(2, 4)));
+        assert_eq!(
+            (parsed.action, parsed.args.as_str(), parsed.value.as_deref()),
+            ("parse_selection", "\"u32\"", Some("12"))
+        );
+
+        // no longer installed
+        Cursor::from("x").text("x");
+        assert_eq!(rec.records().len(), expected.len());
+    }
+
+    #[test]
+    fn test_rule_events() {
+        let g = Grammar::new()
+            .rule("num", |_g, s| {
+                Cursor::from(s).digits(1..).parse_selection().validate()
+            })
+            .rule("sum", |g, s| {
+                let (c, a, b) = Cursor::from(s)
+                    .parse_with(|s| g.parse("num", s))
+                    .text("+")
+                    .parse_with(|s| g.parse("num", s))
+                    .validate()?;
+                Ok((c, a + b))
+            });
+        let rec = Recorder::new();
+        let res = with_tracer(rec.clone(), || g.parse("sum", "1+2"));
+        assert_eq!(res.unwrap(), ("", 3));
+        let rules: Vec<_> = rec
+            .records()
+            .into_iter()
+            .filter(|r| matches!(r.kind, EventKind::Enter | EventKind::Exit))
+            .map(|r| format!("{:?} {} {} {:?}", r.kind, r.action, r.offset, r.error))
+            .collect();
+        assert_eq!(
+            rules,
+            [
+                "Enter sum 0 None",
+                "Enter num 0 None",
+                "Exit num 1 None",
+                "Enter num 2 None",
+                "Exit num 3 None",
+                "Exit sum 3 None",
+            ]
+        );
+    }
+}